@@ -0,0 +1,132 @@
+use std::io;
+
+use serde_json::json;
+
+pub const MOJANG_SESSION_SERVER: &str = "https://sessionserver.mojang.com";
+
+// Called during the encryption handshake, before the Encryption Response is sent, so the
+// server can check with the session server that we really own the account
+pub trait SessionAuthenticator {
+    fn join(&self, server_hash: &str) -> io::Result<()>;
+}
+
+// for offline-mode servers (or proxies) that encrypt without checking the session
+pub struct OfflineAuthenticator;
+
+impl SessionAuthenticator for OfflineAuthenticator {
+    fn join(&self, _server_hash: &str) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub struct SessionServerAuthenticator {
+    pub session_server: String, // base url, MOJANG_SESSION_SERVER or a local stand-in
+    pub access_token: String,
+    pub profile_uuid: String, // without hyphens
+}
+
+impl SessionAuthenticator for SessionServerAuthenticator {
+    fn join(&self, server_hash: &str) -> io::Result<()> {
+        let url = format!(
+            "{}/session/minecraft/join",
+            self.session_server.trim_end_matches('/')
+        );
+        let body = json!({
+            "accessToken": self.access_token,
+            "selectedProfile": self.profile_uuid,
+            "serverId": server_hash,
+        });
+
+        match ureq::post(&url)
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+        {
+            Ok(_) => Ok(()), // 204 No Content
+            Err(ureq::Error::Status(code, response)) => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "Session server refused to join ({}): {}",
+                    code,
+                    response.into_string().unwrap_or_default()
+                ),
+            )),
+            Err(error) => Err(io::Error::other(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // answers a single request with the given status line and returns the request body
+    fn stand_in_session_server(status: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            assert_eq!("POST /session/minecraft/join HTTP/1.1", request_line.trim());
+
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let mut stream = reader.into_inner();
+            write!(stream, "{}\r\nContent-Length: 0\r\n\r\n", status).unwrap();
+
+            String::from_utf8(body).unwrap()
+        });
+
+        (address, handle)
+    }
+
+    fn authenticator(session_server: String) -> SessionServerAuthenticator {
+        SessionServerAuthenticator {
+            session_server,
+            access_token: "token".to_string(),
+            profile_uuid: "069a79f444e94726a5befca90e38aaf5".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_join_accepted() {
+        let (address, handle) = stand_in_session_server("HTTP/1.1 204 No Content");
+
+        authenticator(address).join("-7c9d5b00").unwrap();
+
+        let body: serde_json::Value = serde_json::from_str(&handle.join().unwrap()).unwrap();
+        assert_eq!("token", body["accessToken"]);
+        assert_eq!("069a79f444e94726a5befca90e38aaf5", body["selectedProfile"]);
+        assert_eq!("-7c9d5b00", body["serverId"]);
+    }
+
+    #[test]
+    fn test_join_refused() {
+        let (address, handle) = stand_in_session_server("HTTP/1.1 403 Forbidden");
+
+        let error = authenticator(address).join("abc").unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, error.kind());
+        handle.join().unwrap();
+    }
+}
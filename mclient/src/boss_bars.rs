@@ -0,0 +1,221 @@
+use crate::chat::ChatComponent;
+use crate::packet::{BossBar, BossBarAction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossBarColor {
+    Pink,
+    Blue,
+    Red,
+    Green,
+    Yellow,
+    Purple,
+    White,
+}
+
+impl BossBarColor {
+    // unknown colors are drawn white
+    pub fn from_id(id: i32) -> BossBarColor {
+        match id {
+            0 => BossBarColor::Pink,
+            1 => BossBarColor::Blue,
+            2 => BossBarColor::Red,
+            3 => BossBarColor::Green,
+            4 => BossBarColor::Yellow,
+            5 => BossBarColor::Purple,
+            _ => BossBarColor::White,
+        }
+    }
+
+    pub fn ansi(self) -> &'static str {
+        match self {
+            BossBarColor::Pink => "\x1B[95m",
+            BossBarColor::Blue => "\x1B[94m",
+            BossBarColor::Red => "\x1B[91m",
+            BossBarColor::Green => "\x1B[92m",
+            BossBarColor::Yellow => "\x1B[93m",
+            BossBarColor::Purple => "\x1B[35m",
+            BossBarColor::White => "\x1B[97m",
+        }
+    }
+}
+
+// how many segments the notches split the bar in
+pub fn division_segments(division: i32) -> usize {
+    match division {
+        1 => 6,
+        2 => 10,
+        3 => 12,
+        4 => 20,
+        _ => 1,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveBossBar {
+    pub uuid: u128,
+    pub title: ChatComponent,
+    pub health: f32, // 0 to 1
+    pub color: BossBarColor,
+    pub segments: usize,
+    pub flags: u8,
+}
+
+impl ActiveBossBar {
+    // the bar as `width` cells, the filled ones in the bar color, with a notch between segments
+    pub fn to_ansi(&self, width: usize) -> String {
+        let filled = (self.health.clamp(0.0, 1.0) * width as f32).round() as usize;
+        let mut bar = String::from(self.color.ansi());
+        for cell in 0..width {
+            if cell == filled {
+                bar.push_str("\x1B[90m");
+            }
+            let segment = |cell: usize| cell * self.segments / width;
+            let notch = cell > 0 && segment(cell) != segment(cell - 1);
+            bar.push(match (cell < filled, notch) {
+                (_, true) => '\u{2503}',
+                (true, false) => '\u{2588}',
+                (false, false) => '\u{2591}',
+            });
+        }
+        bar.push_str("\x1B[0m");
+        bar
+    }
+}
+
+// The boss bars on screen, in the order they were added like the vanilla client stacks them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BossBars {
+    bars: Vec<ActiveBossBar>,
+}
+
+impl BossBars {
+    pub fn new() -> BossBars {
+        BossBars::default()
+    }
+
+    // updates for bars that were never added are ignored
+    pub fn apply(&mut self, packet: BossBar) {
+        let uuid = packet.uuid;
+        match packet.action {
+            BossBarAction::Add {
+                title,
+                health,
+                color,
+                division,
+                flags,
+            } => {
+                self.bars.retain(|bar| bar.uuid != uuid);
+                self.bars.push(ActiveBossBar {
+                    uuid,
+                    title: ChatComponent::from_json_lossy(&title),
+                    health,
+                    color: BossBarColor::from_id(color),
+                    segments: division_segments(division),
+                    flags,
+                });
+            }
+            BossBarAction::Remove => self.bars.retain(|bar| bar.uuid != uuid),
+            update => {
+                let Some(bar) = self.bars.iter_mut().find(|bar| bar.uuid == uuid) else {
+                    return;
+                };
+                match update {
+                    BossBarAction::UpdateHealth(health) => bar.health = health,
+                    BossBarAction::UpdateTitle(title) => {
+                        bar.title = ChatComponent::from_json_lossy(&title)
+                    }
+                    BossBarAction::UpdateStyle { color, division } => {
+                        bar.color = BossBarColor::from_id(color);
+                        bar.segments = division_segments(division);
+                    }
+                    BossBarAction::UpdateFlags(flags) => bar.flags = flags,
+                    BossBarAction::Add { .. } | BossBarAction::Remove => {}
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, uuid: u128) -> Option<&ActiveBossBar> {
+        self.bars.iter().find(|bar| bar.uuid == uuid)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ActiveBossBar> {
+        self.bars.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(uuid: u128, action: BossBarAction) -> BossBar {
+        BossBar { uuid, action }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut bars = BossBars::new();
+        for uuid in [1, 2] {
+            bars.apply(packet(
+                uuid,
+                BossBarAction::Add {
+                    title: r#"{"text":"Event starts in 60s"}"#.to_string(),
+                    health: 1.0,
+                    color: 0,
+                    division: 0,
+                    flags: 0,
+                },
+            ));
+        }
+        bars.apply(packet(1, BossBarAction::UpdateHealth(0.5)));
+        bars.apply(packet(
+            1,
+            BossBarAction::UpdateTitle(r#""Event starts in 30s""#.to_string()),
+        ));
+        bars.apply(packet(
+            1,
+            BossBarAction::UpdateStyle {
+                color: 2,
+                division: 1,
+            },
+        ));
+        bars.apply(packet(9, BossBarAction::UpdateHealth(0.0)));
+        bars.apply(packet(2, BossBarAction::Remove));
+
+        assert_eq!(1, bars.len());
+        let bar = bars.get(1).unwrap();
+        assert_eq!(0.5, bar.health);
+        assert_eq!(ChatComponent::text("Event starts in 30s"), bar.title);
+        assert_eq!(BossBarColor::Red, bar.color);
+        assert_eq!(6, bar.segments);
+        assert_eq!(None, bars.get(9));
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        let mut bar = ActiveBossBar {
+            uuid: 1,
+            title: ChatComponent::text(""),
+            health: 0.5,
+            color: BossBarColor::Green,
+            segments: 1,
+            flags: 0,
+        };
+        assert_eq!(
+            "\x1B[92m\u{2588}\u{2588}\x1B[90m\u{2591}\u{2591}\x1B[0m",
+            bar.to_ansi(4)
+        );
+
+        bar.segments = 2;
+        bar.health = 1.0;
+        assert_eq!("\x1B[92m\u{2588}\u{2503}\x1B[0m", bar.to_ansi(2));
+    }
+}
@@ -0,0 +1,568 @@
+use std::io;
+
+use serde_json::{Map, Value};
+
+use crate::translation::{parse_format, FormatPiece, Translations};
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    // "#rrggbb", since 1.16
+    Rgb(u8, u8, u8),
+}
+
+// in the order of the legacy § codes 0-f
+const NAMED_COLORS: [(&str, Color); 16] = [
+    ("black", Color::Black),
+    ("dark_blue", Color::DarkBlue),
+    ("dark_green", Color::DarkGreen),
+    ("dark_aqua", Color::DarkAqua),
+    ("dark_red", Color::DarkRed),
+    ("dark_purple", Color::DarkPurple),
+    ("gold", Color::Gold),
+    ("gray", Color::Gray),
+    ("dark_gray", Color::DarkGray),
+    ("blue", Color::Blue),
+    ("green", Color::Green),
+    ("aqua", Color::Aqua),
+    ("red", Color::Red),
+    ("light_purple", Color::LightPurple),
+    ("yellow", Color::Yellow),
+    ("white", Color::White),
+];
+
+impl Color {
+    pub fn parse(name: &str) -> Option<Color> {
+        if let Some(hex) = name.strip_prefix('#') {
+            let rgb = u32::from_str_radix(hex, 16)
+                .ok()
+                .filter(|_| hex.len() == 6)?;
+            return Some(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+        }
+
+        NAMED_COLORS
+            .iter()
+            .find(|(color_name, _)| *color_name == name)
+            .map(|(_, color)| *color)
+    }
+
+    pub fn legacy_code(code: char) -> Option<Color> {
+        let index = code.to_digit(16)?;
+        Some(NAMED_COLORS[index as usize].1)
+    }
+
+    // the numbering of team colors, the same as the § codes
+    pub fn from_index(index: i32) -> Option<Color> {
+        let index = usize::try_from(index).ok()?;
+        NAMED_COLORS.get(index).map(|(_, color)| *color)
+    }
+
+    pub fn ansi(&self) -> String {
+        let code = match self {
+            Color::Black => "30",
+            Color::DarkBlue => "34",
+            Color::DarkGreen => "32",
+            Color::DarkAqua => "36",
+            Color::DarkRed => "31",
+            Color::DarkPurple => "35",
+            Color::Gold => "33",
+            Color::Gray => "37",
+            Color::DarkGray => "90",
+            Color::Blue => "94",
+            Color::Green => "92",
+            Color::Aqua => "96",
+            Color::Red => "91",
+            Color::LightPurple => "95",
+            Color::Yellow => "93",
+            Color::White => "97",
+            Color::Rgb(r, g, b) => return format!("\x1B[38;2;{};{};{}m", r, g, b),
+        };
+        format!("\x1B[{}m", code)
+    }
+}
+
+// Every field is optional: what a component does not set it takes from its parent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    pub font: Option<String>,
+    pub insertion: Option<String>,
+}
+
+impl Style {
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            color: self.color.or(parent.color),
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underlined: self.underlined.or(parent.underlined),
+            strikethrough: self.strikethrough.or(parent.strikethrough),
+            obfuscated: self.obfuscated.or(parent.obfuscated),
+            font: self.font.clone().or_else(|| parent.font.clone()),
+            insertion: self.insertion.clone().or_else(|| parent.insertion.clone()),
+        }
+    }
+
+    // obfuscated text has no terminal equivalent and is printed as is
+    pub fn ansi(&self) -> String {
+        let mut codes = String::new();
+        if self.bold == Some(true) {
+            codes.push_str("\x1B[1m");
+        }
+        if self.italic == Some(true) {
+            codes.push_str("\x1B[3m");
+        }
+        if self.underlined == Some(true) {
+            codes.push_str("\x1B[4m");
+        }
+        if self.strikethrough == Some(true) {
+            codes.push_str("\x1B[9m");
+        }
+        if let Some(color) = self.color {
+            codes.push_str(&color.ansi());
+        }
+        codes
+    }
+
+    fn from_json(object: &Map<String, Value>) -> Style {
+        let flag = |name: &str| object.get(name).and_then(Value::as_bool);
+        let string = |name: &str| object.get(name).and_then(Value::as_str).map(str::to_string);
+
+        Style {
+            color: object
+                .get("color")
+                .and_then(Value::as_str)
+                .and_then(Color::parse),
+            bold: flag("bold"),
+            italic: flag("italic"),
+            underlined: flag("underlined"),
+            strikethrough: flag("strikethrough"),
+            obfuscated: flag("obfuscated"),
+            font: string("font"),
+            insertion: string("insertion"),
+        }
+    }
+
+    // §0-§f set a color and clear the added formatting, §k-§o add formatting, §r goes back to `base`
+    fn apply_legacy_code(&mut self, code: char, base: &Style) {
+        if let Some(color) = Color::legacy_code(code) {
+            *self = Style {
+                color: Some(color),
+                ..base.clone()
+            };
+            return;
+        }
+
+        match code.to_ascii_lowercase() {
+            'k' => self.obfuscated = Some(true),
+            'l' => self.bold = Some(true),
+            'm' => self.strikethrough = Some(true),
+            'n' => self.underlined = Some(true),
+            'o' => self.italic = Some(true),
+            'r' => *self = base.clone(),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NbtSource {
+    Block(String),
+    Entity(String),
+    Storage(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Content {
+    Text(String),
+    Translate {
+        key: String,
+        with: Vec<ChatComponent>,
+    },
+    // the server fills in `value` before sending
+    Score {
+        name: String,
+        objective: String,
+        value: Option<String>,
+    },
+    Selector {
+        selector: String,
+        separator: Option<Box<ChatComponent>>,
+    },
+    Keybind(String),
+    Nbt {
+        path: String,
+        interpret: bool,
+        separator: Option<Box<ChatComponent>>,
+        source: Option<NbtSource>,
+    },
+}
+
+// A chat component as sent by the server in json, see https://wiki.vg/Chat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatComponent {
+    pub content: Content,
+    pub style: Style,
+    pub extra: Vec<ChatComponent>,
+}
+
+impl ChatComponent {
+    pub fn text(text: &str) -> ChatComponent {
+        ChatComponent {
+            content: Content::Text(text.to_string()),
+            style: Style::default(),
+            extra: vec![],
+        }
+    }
+
+    pub fn from_json(json: &str) -> io::Result<ChatComponent> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|error| invalid(error.to_string()))?;
+        ChatComponent::from_value(&value)
+    }
+
+    // for display: json that is not a valid component is shown as it is
+    pub fn from_json_lossy(json: &str) -> ChatComponent {
+        ChatComponent::from_json(json).unwrap_or_else(|_| ChatComponent::text(json))
+    }
+
+    // empty text without children, what servers send to clear a header or a title
+    pub fn is_empty(&self) -> bool {
+        matches!(&self.content, Content::Text(text) if text.is_empty())
+            && self.extra.iter().all(ChatComponent::is_empty)
+    }
+
+    // a component is a string, an array (the first element is the parent of the others)
+    // or an object with one of the content keys
+    pub fn from_value(value: &Value) -> io::Result<ChatComponent> {
+        match value {
+            Value::String(text) => Ok(ChatComponent::text(text)),
+            Value::Number(_) | Value::Bool(_) => Ok(ChatComponent::text(&value.to_string())),
+            Value::Array(values) => {
+                let mut values = values.iter();
+                let mut component = match values.next() {
+                    Some(first) => ChatComponent::from_value(first)?,
+                    None => ChatComponent::text(""),
+                };
+                for value in values {
+                    component.extra.push(ChatComponent::from_value(value)?);
+                }
+                Ok(component)
+            }
+            Value::Object(object) => ChatComponent::from_object(object),
+            Value::Null => Err(invalid("Chat component is null".to_string())),
+        }
+    }
+
+    fn from_object(object: &Map<String, Value>) -> io::Result<ChatComponent> {
+        let string = |name: &str| object.get(name).and_then(Value::as_str).map(str::to_string);
+        let components = |name: &str| -> io::Result<Vec<ChatComponent>> {
+            match object.get(name) {
+                Some(Value::Array(values)) => {
+                    values.iter().map(ChatComponent::from_value).collect()
+                }
+                Some(value) => Err(invalid(format!(
+                    "Chat component {} is not a list: {}",
+                    name, value
+                ))),
+                None => Ok(vec![]),
+            }
+        };
+        let separator = || -> io::Result<Option<Box<ChatComponent>>> {
+            object
+                .get("separator")
+                .map(|value| ChatComponent::from_value(value).map(Box::new))
+                .transpose()
+        };
+
+        let content = if let Some(text) = object.get("text") {
+            match text {
+                Value::String(text) => Content::Text(text.clone()),
+                _ => Content::Text(text.to_string()),
+            }
+        } else if let Some(key) = string("translate") {
+            Content::Translate {
+                key,
+                with: components("with")?,
+            }
+        } else if let Some(score) = object.get("score").and_then(Value::as_object) {
+            let field = |name: &str| score.get(name).and_then(Value::as_str).map(str::to_string);
+            Content::Score {
+                name: field("name").unwrap_or_default(),
+                objective: field("objective").unwrap_or_default(),
+                value: field("value"),
+            }
+        } else if let Some(selector) = string("selector") {
+            Content::Selector {
+                selector,
+                separator: separator()?,
+            }
+        } else if let Some(keybind) = string("keybind") {
+            Content::Keybind(keybind)
+        } else if let Some(path) = string("nbt") {
+            let source = if let Some(block) = string("block") {
+                Some(NbtSource::Block(block))
+            } else if let Some(entity) = string("entity") {
+                Some(NbtSource::Entity(entity))
+            } else {
+                string("storage").map(NbtSource::Storage)
+            };
+            Content::Nbt {
+                path,
+                interpret: object
+                    .get("interpret")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                separator: separator()?,
+                source,
+            }
+        } else {
+            return Err(invalid(format!(
+                "Chat component without content: {}",
+                Value::Object(object.clone())
+            )));
+        };
+
+        Ok(ChatComponent {
+            content,
+            style: Style::from_json(object),
+            extra: components("extra")?,
+        })
+    }
+
+    // Flattens the component tree into runs of text, each with its fully inherited style
+    pub fn spans(&self, translations: &Translations) -> Vec<(String, Style)> {
+        let mut spans = vec![];
+        self.push_spans(translations, &Style::default(), &mut spans);
+        spans
+    }
+
+    fn push_spans(
+        &self,
+        translations: &Translations,
+        parent: &Style,
+        spans: &mut Vec<(String, Style)>,
+    ) {
+        let style = self.style.inherit(parent);
+
+        match &self.content {
+            Content::Text(text) => push_legacy_text(text, &style, spans),
+            Content::Translate { key, with } => {
+                // unknown keys are shown as the key, like the vanilla client does
+                let format = translations.get(key).unwrap_or(key);
+                for piece in parse_format(format) {
+                    match piece {
+                        FormatPiece::Literal(text) => push_legacy_text(&text, &style, spans),
+                        FormatPiece::Argument { index, placeholder } => match with.get(index) {
+                            Some(argument) => argument.push_spans(translations, &style, spans),
+                            None => push_legacy_text(placeholder, &style, spans),
+                        },
+                    }
+                }
+            }
+            Content::Score { value, .. } => {
+                push_legacy_text(value.as_deref().unwrap_or_default(), &style, spans)
+            }
+            Content::Selector { selector, .. } => push_legacy_text(selector, &style, spans),
+            Content::Keybind(keybind) => push_legacy_text(keybind, &style, spans),
+            Content::Nbt { path, .. } => push_legacy_text(path, &style, spans),
+        }
+
+        for child in &self.extra {
+            child.push_spans(translations, &style, spans);
+        }
+    }
+
+    // the text with the style as ANSI escape codes, ending with a reset
+    pub fn to_ansi(&self, translations: &Translations) -> String {
+        let mut ansi = String::new();
+        for (text, style) in self.spans(translations) {
+            ansi.push_str("\x1B[0m");
+            ansi.push_str(&style.ansi());
+            ansi.push_str(&text);
+        }
+        ansi.push_str("\x1B[0m");
+        ansi
+    }
+
+    pub fn to_plain(&self, translations: &Translations) -> String {
+        self.spans(translations)
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect()
+    }
+}
+
+// splits text at legacy § formatting codes, which plugins still put inside components
+fn push_legacy_text(text: &str, base: &Style, spans: &mut Vec<(String, Style)>) {
+    let mut style = base.clone();
+    let mut current = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '§' {
+            current.push(c);
+            continue;
+        }
+
+        let Some(code) = chars.next() else {
+            break;
+        };
+        if !current.is_empty() {
+            spans.push((std::mem::take(&mut current), style.clone()));
+        }
+        style.apply_legacy_code(code, base);
+    }
+
+    if !current.is_empty() {
+        spans.push((current, style));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let component = ChatComponent::from_json(
+            r##"{"text":"Hello ","color":"#ff8000","bold":true,"extra":["world",{"keybind":"key.jump","italic":true}]}"##,
+        )
+        .unwrap();
+        assert_eq!(Content::Text("Hello ".to_string()), component.content);
+        assert_eq!(Some(Color::Rgb(0xff, 0x80, 0x00)), component.style.color);
+        assert_eq!(Some(true), component.style.bold);
+        assert_eq!(2, component.extra.len());
+        assert_eq!(
+            Content::Keybind("key.jump".to_string()),
+            component.extra[1].content
+        );
+
+        let array = ChatComponent::from_json(r#"["a",{"text":"b"},3]"#).unwrap();
+        assert_eq!("ab3", array.to_plain(&Translations::default()));
+        assert!(!array.is_empty());
+        assert!(ChatComponent::from_json(r#"{"text":"","extra":[""]}"#)
+            .unwrap()
+            .is_empty());
+
+        let score = ChatComponent::from_json(
+            r#"{"score":{"name":"Steve","objective":"kills","value":"12"}}"#,
+        )
+        .unwrap();
+        assert_eq!("12", score.to_plain(&Translations::default()));
+
+        let nbt = ChatComponent::from_json(r#"{"nbt":"Items[0]","entity":"@s","interpret":true}"#)
+            .unwrap();
+        assert!(matches!(
+            nbt.content,
+            Content::Nbt {
+                interpret: true,
+                source: Some(NbtSource::Entity(_)),
+                ..
+            }
+        ));
+
+        assert!(ChatComponent::from_json(r#"{"color":"red"}"#).is_err());
+        assert!(ChatComponent::from_json(r#"{"text":"a","extra":"b"}"#).is_err());
+    }
+
+    #[test]
+    fn test_inheritance() {
+        let component = ChatComponent::from_json(
+            r#"{"text":"","color":"red","extra":[{"text":"a","bold":true,"extra":["b"]},{"text":"c","color":"blue"}]}"#,
+        )
+        .unwrap();
+        let spans = component.spans(&Translations::default());
+
+        assert_eq!(3, spans.len());
+        assert_eq!(
+            ("a".to_string(), Some(Color::Red), Some(true)),
+            (spans[0].0.clone(), spans[0].1.color, spans[0].1.bold)
+        );
+        assert_eq!(
+            (Some(Color::Red), Some(true)),
+            (spans[1].1.color, spans[1].1.bold)
+        );
+        assert_eq!(
+            (Some(Color::Blue), None),
+            (spans[2].1.color, spans[2].1.bold)
+        );
+    }
+
+    #[test]
+    fn test_translate() {
+        let chat = ChatComponent::from_json(
+            r#"{"translate":"chat.type.text","with":[{"text":"Steve","insertion":"Steve"},"hi 100%"]}"#,
+        )
+        .unwrap();
+        assert_eq!("<Steve> hi 100%", chat.to_plain(&Translations::default()));
+
+        let numbered = ChatComponent {
+            content: Content::Translate {
+                key: "%2$s before %1$s, %% and %s %s %s".to_string(),
+                with: vec![ChatComponent::text("one"), ChatComponent::text("two")],
+            },
+            style: Style::default(),
+            extra: vec![],
+        };
+        assert_eq!(
+            "two before one, % and one two %s",
+            numbered.to_plain(&Translations::default())
+        );
+
+        let death = ChatComponent::from_json(
+            r#"{"translate":"death.attack.player","with":[{"text":"Steve"},{"text":"Alex"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            "Steve was slain by Alex",
+            death.to_plain(&Translations::default())
+        );
+
+        let unknown =
+            ChatComponent::from_json(r#"{"translate":"advancements.story.root.title"}"#).unwrap();
+        assert_eq!(
+            "advancements.story.root.title",
+            unknown.to_plain(&Translations::default())
+        );
+    }
+
+    #[test]
+    fn test_ansi() {
+        let component =
+            ChatComponent::from_json(r#"{"text":"§cred §lbold§r plain","underlined":true}"#)
+                .unwrap();
+        assert_eq!(
+            "red bold plain",
+            component.to_plain(&Translations::default())
+        );
+        assert_eq!(
+            "\x1B[0m\x1B[4m\x1B[91mred \x1B[0m\x1B[1m\x1B[4m\x1B[91mbold\x1B[0m\x1B[4m plain\x1B[0m",
+            component.to_ansi(&Translations::default())
+        );
+    }
+}
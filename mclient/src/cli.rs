@@ -0,0 +1,272 @@
+use std::io;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use mclient::auth::*;
+use mclient::packet::*;
+use mclient::translation::Translations;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Mode {
+    /// Only ping the server, print the status and save the favicon
+    Status,
+    /// Log in and stay connected reading and sending chat messages
+    Chat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ChatMode {
+    Enabled,
+    CommandsOnly,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MainHand {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Parser)]
+#[command(version, about = "Rust CLI chat client for a Minecraft server")]
+pub struct Args {
+    /// Server address to connect to
+    #[arg(short = 'H', long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Server port
+    #[arg(short, long, default_value_t = 25565)]
+    pub port: u16,
+
+    /// Username used for the offline-mode login
+    #[arg(short, long, default_value = "eudinaltapartee")]
+    pub username: String,
+
+    /// Protocol version to log in with (e.g. 758 = 1.18.2), detected from the server status when not given
+    #[arg(long)]
+    pub protocol_version: Option<i32>,
+
+    /// What the client does after connecting
+    #[arg(short, long, value_enum, default_value_t = Mode::Chat)]
+    pub mode: Mode,
+
+    /// Access token of the account, needed to join online-mode servers
+    #[arg(long, requires = "profile_uuid")]
+    pub access_token: Option<String>,
+
+    /// Profile UUID (without hyphens) the access token belongs to
+    #[arg(long)]
+    pub profile_uuid: Option<String>,
+
+    /// Session server used to authenticate the join
+    #[arg(long, default_value = MOJANG_SESSION_SERVER)]
+    pub session_server: String,
+
+    /// How many times to reconnect after the connection is lost or times out
+    #[arg(long, default_value_t = 0)]
+    pub reconnect: u32,
+
+    /// Minecraft language file (e.g. en_us.json) used to display translated chat messages
+    #[arg(long)]
+    pub lang_file: Option<PathBuf>,
+
+    /// Respawn right away after dying instead of waiting for the respawn command. Applies to the
+    /// whole run, there are no per-profile settings: start one client per profile to mix them
+    #[arg(long)]
+    pub auto_respawn: bool,
+
+    /// Client brand sent to the server on the minecraft:brand channel after joining
+    #[arg(long, default_value = "vanilla")]
+    pub brand: String,
+
+    /// Locale sent in the client settings
+    #[arg(long, default_value = "en_us")]
+    pub locale: String,
+
+    /// View distance in chunks sent in the client settings
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(i8).range(2..=32))]
+    pub view_distance: i8,
+
+    /// Which chat messages the server should send
+    #[arg(long, value_enum, default_value_t = ChatMode::Enabled)]
+    pub chat_mode: ChatMode,
+
+    /// Ask the server to strip the colors of chat messages
+    #[arg(long)]
+    pub no_chat_colors: bool,
+
+    /// Bit mask of the displayed skin parts: cape 1, jacket 2, sleeves 4 and 8, pants 16 and 32, hat 64
+    #[arg(long, default_value_t = ALL_SKIN_PARTS)]
+    pub skin_parts: u8,
+
+    /// Main hand sent in the client settings
+    #[arg(long, value_enum, default_value_t = MainHand::Right)]
+    pub main_hand: MainHand,
+
+    /// Hide the player from the server list of online players (1.18+)
+    #[arg(long)]
+    pub hide_from_server_list: bool,
+}
+
+impl Args {
+    // IPv6 literals need brackets to be told apart from the port
+    pub fn address(&self) -> String {
+        match self.host.contains(':') && !self.host.starts_with('[') {
+            true => format!("[{}]:{}", self.host, self.port),
+            false => format!("{}:{}", self.host, self.port),
+        }
+    }
+
+    pub fn authenticator(&self) -> Box<dyn SessionAuthenticator> {
+        match (&self.access_token, &self.profile_uuid) {
+            (Some(access_token), Some(profile_uuid)) => Box::new(SessionServerAuthenticator {
+                session_server: self.session_server.clone(),
+                access_token: access_token.clone(),
+                profile_uuid: profile_uuid.replace('-', ""),
+            }),
+            _ => Box::new(OfflineAuthenticator),
+        }
+    }
+
+    pub fn client_settings(&self) -> ClientSettings {
+        ClientSettings {
+            locale: self.locale.clone(),
+            view_distance: self.view_distance,
+            chat_mode: match self.chat_mode {
+                ChatMode::Enabled => CHAT_ENABLED,
+                ChatMode::CommandsOnly => CHAT_COMMANDS_ONLY,
+                ChatMode::Hidden => CHAT_HIDDEN,
+            },
+            chat_colors: !self.no_chat_colors,
+            displayed_skin_parts: self.skin_parts,
+            main_hand: match self.main_hand {
+                MainHand::Left => MAIN_HAND_LEFT,
+                MainHand::Right => MAIN_HAND_RIGHT,
+            },
+            text_filtering: false,
+            allow_server_listings: !self.hide_from_server_list,
+        }
+    }
+
+    pub fn translations(&self) -> io::Result<Translations> {
+        match &self.lang_file {
+            Some(path) => Translations::load(path),
+            None => Ok(Translations::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::ToSocketAddrs;
+
+    #[test]
+    fn test_defaults() {
+        let args = Args::parse_from(["mclient"]);
+        assert_eq!("127.0.0.1:25565", args.address());
+        assert_eq!("eudinaltapartee", args.username);
+        assert_eq!(None, args.protocol_version);
+        assert_eq!(Mode::Chat, args.mode);
+        assert_eq!(None, args.access_token);
+        assert_eq!(MOJANG_SESSION_SERVER, args.session_server);
+        assert_eq!(None, args.lang_file);
+        assert_eq!(0, args.reconnect);
+        assert!(!args.auto_respawn);
+        assert_eq!("vanilla", args.brand);
+        assert_eq!(
+            ClientSettings {
+                locale: "en_us".to_string(),
+                view_distance: 10,
+                chat_mode: CHAT_ENABLED,
+                chat_colors: true,
+                displayed_skin_parts: ALL_SKIN_PARTS,
+                main_hand: MAIN_HAND_RIGHT,
+                text_filtering: false,
+                allow_server_listings: true,
+            },
+            args.client_settings()
+        );
+    }
+
+    #[test]
+    fn test_client_settings() {
+        let args = Args::parse_from([
+            "mclient",
+            "--locale",
+            "fr_fr",
+            "--view-distance",
+            "4",
+            "--chat-mode",
+            "commands-only",
+            "--no-chat-colors",
+            "--main-hand",
+            "left",
+            "--brand",
+            "mclient",
+        ]);
+        let settings = args.client_settings();
+        assert_eq!("fr_fr", settings.locale);
+        assert_eq!(4, settings.view_distance);
+        assert_eq!(CHAT_COMMANDS_ONLY, settings.chat_mode);
+        assert!(!settings.chat_colors);
+        assert_eq!(MAIN_HAND_LEFT, settings.main_hand);
+        assert_eq!("mclient", args.brand);
+
+        assert!(Args::try_parse_from(["mclient", "--view-distance", "64"]).is_err());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let args = Args::parse_from([
+            "mclient",
+            "--host",
+            "mc.example.org",
+            "-p",
+            "25570",
+            "-u",
+            "bridge",
+            "--protocol-version",
+            "760",
+            "--mode",
+            "status",
+            "--lang-file",
+            "lang/fr_fr.json",
+            "--auto-respawn",
+        ]);
+        assert_eq!("mc.example.org:25570", args.address());
+        assert_eq!("bridge", args.username);
+        assert_eq!(Some(760), args.protocol_version);
+        assert_eq!(Mode::Status, args.mode);
+        assert_eq!(Some(PathBuf::from("lang/fr_fr.json")), args.lang_file);
+        assert!(args.auto_respawn);
+    }
+
+    #[test]
+    fn test_ipv6_address() {
+        let args = Args::parse_from(["mclient", "-H", "::1"]);
+        assert_eq!("[::1]:25565", args.address());
+        assert!(args.address().to_socket_addrs().is_ok());
+
+        let args = Args::parse_from(["mclient", "-H", "[2001:db8::1]", "-p", "25570"]);
+        assert_eq!("[2001:db8::1]:25570", args.address());
+    }
+
+    #[test]
+    fn test_access_token_requires_profile() {
+        assert!(Args::try_parse_from(["mclient", "--access-token", "token"]).is_err());
+
+        let args = Args::parse_from([
+            "mclient",
+            "--access-token",
+            "token",
+            "--profile-uuid",
+            "069a79f444e94726a5befca90e38aaf5",
+            "--session-server",
+            "http://127.0.0.1:8080",
+        ]);
+        assert_eq!(Some("token".to_string()), args.access_token);
+        assert_eq!("http://127.0.0.1:8080", args.session_server);
+    }
+}
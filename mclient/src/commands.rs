@@ -0,0 +1,346 @@
+use crate::packet::{
+    CommandNode, CommandNodeKind, DeclareCommands, ParserProperties, GREEDY_PHRASE,
+};
+
+pub const ASK_SERVER: &str = "minecraft:ask_server";
+
+// What can follow the text typed so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Completion {
+    // the matches replace the text from byte `start` to the end
+    Local { start: usize, matches: Vec<String> },
+    // the server knows the suggestions of the argument, ask it with a Tab-Complete request
+    AskServer,
+}
+
+// how many space separated words an argument takes
+fn argument_words(parser: &str) -> usize {
+    match parser {
+        "minecraft:block_pos" | "minecraft:vec3" => 3,
+        "minecraft:vec2" | "minecraft:column_pos" | "minecraft:rotation" => 2,
+        _ => 1,
+    }
+}
+
+// The commands the server declared, as the tree brigadier parses them with
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandGraph {
+    nodes: Vec<CommandNode>,
+    root: usize,
+}
+
+impl CommandGraph {
+    pub fn new(packet: DeclareCommands) -> CommandGraph {
+        CommandGraph {
+            nodes: packet.nodes,
+            root: packet.root_index as usize,
+        }
+    }
+
+    fn node(&self, index: i32) -> Option<&CommandNode> {
+        self.nodes.get(usize::try_from(index).ok()?)
+    }
+
+    // what can follow a node: its children, or those of the node it redirects to (the
+    // `run` of execute goes back to the root)
+    fn children(&self, node: &CommandNode) -> Vec<&CommandNode> {
+        let parent = match node.redirect.and_then(|redirect| self.node(redirect)) {
+            Some(target) if node.children.is_empty() => target,
+            _ => node,
+        };
+        parent
+            .children
+            .iter()
+            .filter_map(|child| self.node(*child))
+            .collect()
+    }
+
+    // the names of the top level commands
+    pub fn commands(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .nodes
+            .get(self.root)
+            .map(|root| self.children(root))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|node| match &node.kind {
+                CommandNodeKind::Literal(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    // the completions of a command line starting with '/', `players` are the names suggested
+    // for entity arguments
+    pub fn complete(&self, input: &str, players: &[&str]) -> Completion {
+        let none = Completion::Local {
+            start: input.len(),
+            matches: vec![],
+        };
+        let (Some(text), Some(mut node)) = (input.strip_prefix('/'), self.nodes.get(self.root))
+        else {
+            return none;
+        };
+
+        // the words with their byte offset in the input, the last one is being typed
+        let mut words = vec![];
+        let mut offset = 1;
+        for word in text.split(' ') {
+            words.push((offset, word));
+            offset += word.len() + 1;
+        }
+
+        let mut index = 0;
+        loop {
+            let children = self.children(node);
+            let (start, partial) = words[index];
+            if index == words.len() - 1 {
+                return complete_word(&children, start, partial, players);
+            }
+
+            let literal = children.iter().find(
+                |child| matches!(&child.kind, CommandNodeKind::Literal(name) if name == partial),
+            );
+            if let Some(literal) = literal {
+                node = literal;
+                index += 1;
+                continue;
+            }
+
+            let argument = children.iter().find_map(|child| match &child.kind {
+                CommandNodeKind::Argument {
+                    parser, properties, ..
+                } => Some((*child, parser, properties)),
+                _ => None,
+            });
+            let Some((argument, parser, properties)) = argument else {
+                return none;
+            };
+
+            // a greedy string takes the rest of the line, an argument that takes more words
+            // than are left is still being typed
+            let last = words.len() - 1;
+            if *properties == ParserProperties::String(GREEDY_PHRASE) {
+                return complete_word(&[argument], start, &input[start..], players);
+            }
+            index += argument_words(parser);
+            if index > last {
+                let (start, partial) = words[last];
+                return complete_word(&[argument], start, partial, players);
+            }
+            node = argument;
+        }
+    }
+}
+
+// brigadier indexes the text in UTF-16 code units like java strings do, this is the byte
+// index of the same position (the end of the text for indices past it)
+pub fn utf16_to_byte_index(text: &str, index: usize) -> usize {
+    let mut units = 0;
+    for (byte, c) in text.char_indices() {
+        if units >= index {
+            return byte;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+// chat messages complete the player name being typed, like the vanilla chat does
+pub fn complete_chat(input: &str, players: &[&str]) -> Completion {
+    let start = input.rfind(' ').map_or(0, |space| space + 1);
+    let lowercase = input[start..].to_lowercase();
+    let mut matches: Vec<String> = players
+        .iter()
+        .filter(|name| name.to_lowercase().starts_with(&lowercase))
+        .map(|name| name.to_string())
+        .collect();
+    matches.sort_unstable();
+
+    Completion::Local { start, matches }
+}
+
+fn complete_word(
+    candidates: &[&CommandNode],
+    start: usize,
+    partial: &str,
+    players: &[&str],
+) -> Completion {
+    let lowercase = partial.to_lowercase();
+    let mut matches = vec![];
+    for candidate in candidates {
+        match &candidate.kind {
+            CommandNodeKind::Literal(name) => {
+                if name.to_lowercase().starts_with(&lowercase) {
+                    matches.push(name.clone());
+                }
+            }
+            CommandNodeKind::Argument {
+                parser,
+                suggestions,
+                ..
+            } => {
+                if suggestions.as_deref() == Some(ASK_SERVER) {
+                    return Completion::AskServer;
+                }
+                let values: Vec<&str> = match parser.as_str() {
+                    "minecraft:entity" | "minecraft:game_profile" | "minecraft:score_holder" => {
+                        players.to_vec()
+                    }
+                    "brigadier:bool" => vec!["true", "false"],
+                    _ => vec![],
+                };
+                matches.extend(
+                    values
+                        .into_iter()
+                        .filter(|value| value.to_lowercase().starts_with(&lowercase))
+                        .map(str::to_string),
+                );
+            }
+            CommandNodeKind::Root => {}
+        }
+    }
+    matches.sort_unstable();
+    matches.dedup();
+
+    Completion::Local { start, matches }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(name: &str, children: Vec<i32>, redirect: Option<i32>) -> CommandNode {
+        CommandNode {
+            kind: CommandNodeKind::Literal(name.to_string()),
+            executable: children.is_empty(),
+            children,
+            redirect,
+        }
+    }
+
+    fn argument(
+        name: &str,
+        parser: &str,
+        properties: ParserProperties,
+        suggestions: Option<&str>,
+        children: Vec<i32>,
+    ) -> CommandNode {
+        CommandNode {
+            kind: CommandNodeKind::Argument {
+                name: name.to_string(),
+                parser: parser.to_string(),
+                properties,
+                suggestions: suggestions.map(str::to_string),
+            },
+            executable: true,
+            children,
+            redirect: None,
+        }
+    }
+
+    // /gamemode <survival|creative> [target], /give <item>, /say <message>,
+    // /tp <x y z>, /execute run ...
+    fn graph() -> CommandGraph {
+        CommandGraph::new(DeclareCommands {
+            nodes: vec![
+                CommandNode {
+                    kind: CommandNodeKind::Root,
+                    executable: false,
+                    children: vec![1, 5, 7, 9, 11],
+                    redirect: None,
+                },
+                literal("gamemode", vec![2, 3], None),
+                literal("survival", vec![4], None),
+                literal("creative", vec![4], None),
+                argument(
+                    "target",
+                    "minecraft:entity",
+                    ParserProperties::Flags(0x02),
+                    None,
+                    vec![],
+                ),
+                literal("give", vec![6], None),
+                argument(
+                    "item",
+                    "minecraft:item_stack",
+                    ParserProperties::None,
+                    Some(ASK_SERVER),
+                    vec![],
+                ),
+                literal("say", vec![8], None),
+                argument(
+                    "message",
+                    "brigadier:string",
+                    ParserProperties::String(GREEDY_PHRASE),
+                    None,
+                    vec![],
+                ),
+                literal("tp", vec![10], None),
+                argument(
+                    "location",
+                    "minecraft:vec3",
+                    ParserProperties::None,
+                    None,
+                    vec![],
+                ),
+                literal("execute", vec![12], None),
+                literal("run", vec![], Some(0)),
+            ],
+            root_index: 0,
+        })
+    }
+
+    fn local(start: usize, matches: &[&str]) -> Completion {
+        Completion::Local {
+            start,
+            matches: matches.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_complete() {
+        let graph = graph();
+        let players = ["Steve", "Alex", "steve2"];
+
+        assert_eq!(
+            vec!["execute", "gamemode", "give", "say", "tp"],
+            graph.commands()
+        );
+        assert_eq!(
+            local(1, &["gamemode", "give"]),
+            graph.complete("/g", &players)
+        );
+        assert_eq!(
+            local(10, &["creative"]),
+            graph.complete("/gamemode c", &players)
+        );
+        assert_eq!(
+            local(19, &["Steve", "steve2"]),
+            graph.complete("/gamemode survival st", &players)
+        );
+        assert_eq!(Completion::AskServer, graph.complete("/give dia", &players));
+        assert_eq!(local(5, &[]), graph.complete("/say hello there", &players));
+        assert_eq!(local(6, &[]), graph.complete("/tp 1 2", &players));
+        assert_eq!(
+            local(13, &["gamemode", "give"]),
+            graph.complete("/execute run g", &players)
+        );
+        assert_eq!(local(7, &[]), graph.complete("/nope x", &players));
+        assert_eq!(local(5, &[]), graph.complete("hello", &players));
+
+        assert_eq!(local(10, &["Alex"]), complete_chat("thanks gg a", &players));
+    }
+
+    #[test]
+    fn test_utf16_to_byte_index() {
+        let text = "/msg é😀 hi";
+        assert_eq!(5, utf16_to_byte_index(text, 5));
+        assert_eq!(7, utf16_to_byte_index(text, 6));
+        // the emoji is two UTF-16 units and four bytes
+        assert_eq!(11, utf16_to_byte_index(text, 8));
+        assert_eq!(text.len(), utf16_to_byte_index(text, 100));
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::{self, Read, Write};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::reader::PacketReader;
+use crate::varint_type::*;
+
+// the vanilla server refuses packets that inflate to more than this
+const MAX_UNCOMPRESSED_LENGTH: usize = 8388608;
+
+// Turns the packet data (id + fields) into the body of a compressed frame:
+//   data length (varint) - 0 when the data is sent uncompressed
+//   data (zlib compressed when data length is not 0)
+// the packet length prefix is added by the caller
+pub fn compress_packet(data: &[u8], threshold: usize) -> io::Result<Vec<u8>> {
+    if data.len() < threshold {
+        let mut body: Vec<u8> = varint_write(0);
+        body.extend_from_slice(data);
+        return Ok(body);
+    }
+
+    let mut encoder = ZlibEncoder::new(varint_write(data.len() as i32), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+// Inverse of compress_packet, returns the packet data (id + fields)
+pub fn decompress_packet(body: &[u8], threshold: usize) -> io::Result<Vec<u8>> {
+    let mut reader = PacketReader::new(body);
+    let data_length = reader.read_varint()?;
+    let body = reader.rest();
+
+    if data_length == 0 {
+        return Ok(body.to_vec());
+    }
+
+    let data_length = data_length as usize;
+    if data_length < threshold || data_length > MAX_UNCOMPRESSED_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid compressed data length {}", data_length),
+        ));
+    }
+
+    let mut data: Vec<u8> = Vec::with_capacity(data_length);
+    ZlibDecoder::new(body)
+        .take(data_length as u64 + 1)
+        .read_to_end(&mut data)?;
+
+    if data.len() != data_length {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Compressed packet inflated to {} bytes instead of {}",
+                data.len(),
+                data_length
+            ),
+        ));
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_below_threshold() {
+        let data = vec![0x0f, 0x01, 0x02];
+        let body = compress_packet(&data, 256).unwrap();
+        assert_eq!(vec![0x00, 0x0f, 0x01, 0x02], body);
+        assert_eq!(data, decompress_packet(&body, 256).unwrap());
+    }
+
+    #[test]
+    fn test_above_threshold() {
+        let data: Vec<u8> = (0..1000).map(|i| (i % 7) as u8).collect();
+        let mut body = compress_packet(&data, 256).unwrap();
+        assert!(body.len() < data.len());
+
+        assert_eq!(1000, varint_read(&mut body.as_slice()).unwrap());
+
+        assert_eq!(data, decompress_packet(&body, 256).unwrap());
+
+        // a wrong data length must not be accepted
+        body.splice(..2, varint_write(999));
+        assert!(decompress_packet(&body, 256).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use crate::compression::*;
+use crate::encryption::CipherStream;
+use crate::error::{Error, Result};
+use crate::packet::*;
+use crate::reader::FrameReader;
+use crate::varint_type::*;
+use crate::version::ProtocolVersion;
+
+// a packet as read from the connection, kind is None for packets the client does not know
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPacket {
+    pub id: i32,
+    pub kind: Option<PacketKind>,
+    pub protocol: i32,
+    pub data: Vec<u8>, // the fields, after the id
+}
+
+impl RawPacket {
+    pub fn decode<P: Packet>(&self) -> Result<P> {
+        P::from_bytes(&self.data, self.protocol).map_err(|source| Error::Decode {
+            packet: P::KIND,
+            source,
+        })
+    }
+}
+
+// Wraps the stream and frames packets on it, taking care of compression once the
+// server sent Set Compression and of encryption after the Encryption Response
+pub struct Connection<S: Read + Write = TcpStream> {
+    frames: FrameReader<CipherStream<S>>, // writes go to the inner CipherStream
+    compression_threshold: Option<usize>,
+    version: &'static ProtocolVersion,
+    state: ConnectionState,
+    last_received: Instant,
+}
+
+// the server sends a Keep Alive every 15 seconds, so this long without data means it is gone
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+// how long a socket read waits, poll_packet returns after at most this long without data
+pub const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl Connection<TcpStream> {
+    pub fn connect(address: &str, version: &'static ProtocolVersion) -> Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        Ok(Connection::new(stream, version))
+    }
+}
+
+impl<S: Read + Write> Connection<S> {
+    pub fn new(stream: S, version: &'static ProtocolVersion) -> Self {
+        Connection {
+            frames: FrameReader::new(CipherStream::new(stream)),
+            compression_threshold: None,
+            version,
+            state: ConnectionState::Handshaking,
+            last_received: Instant::now(),
+        }
+    }
+
+    pub fn version(&self) -> &'static ProtocolVersion {
+        self.version
+    }
+
+    pub fn protocol(&self) -> i32 {
+        self.version.protocol
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    // incoming packet ids are looked up in the table of the current state, and only the
+    // packets of that state can be sent
+    pub fn transition(&mut self, next: ConnectionState) -> Result<()> {
+        if !self.state.can_transition_to(next) {
+            return Err(Error::protocol(format!(
+                "Cannot go from the {:?} state to {:?}",
+                self.state, next
+            )));
+        }
+
+        self.state = next;
+        Ok(())
+    }
+
+    fn stream(&mut self) -> &mut CipherStream<S> {
+        self.frames.get_mut()
+    }
+
+    // everything sent and received after this call is encrypted with the shared secret
+    pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) -> Result<()> {
+        // bytes already buffered were decrypted (not at all) with the old state
+        if !self.frames.buffered().is_empty() {
+            return Err(Error::protocol(
+                "Server sent data before encryption was enabled",
+            ));
+        }
+
+        self.stream().enable(shared_secret);
+        Ok(())
+    }
+
+    // a negative threshold disables compression
+    pub fn set_compression(&mut self, threshold: i32) {
+        self.compression_threshold = if threshold >= 0 {
+            Some(threshold as usize)
+        } else {
+            None
+        };
+    }
+
+    pub fn compression_threshold(&self) -> Option<usize> {
+        self.compression_threshold
+    }
+
+    pub fn write_packet<P: Packet>(&mut self, packet: &P) -> Result<()> {
+        debug_assert_eq!(P::DIRECTION, Direction::Serverbound);
+        if P::STATE != self.state {
+            return Err(Error::protocol(format!(
+                "Cannot send {:?} in the {:?} state",
+                P::KIND,
+                self.state
+            )));
+        }
+
+        let id = self.version.packet_id(P::KIND).ok_or_else(|| {
+            Error::protocol(format!(
+                "{:?} does not exist in {}",
+                P::KIND,
+                self.version.name
+            ))
+        })?;
+
+        let mut data: Vec<u8> = varint_write(id);
+        packet
+            .encode(&mut data, self.version.protocol)
+            .map_err(|source| Error::Encode {
+                packet: P::KIND,
+                source,
+            })?;
+        if let Some(threshold) = self.compression_threshold {
+            data = compress_packet(&data, threshold)?;
+        }
+
+        let mut frame: Vec<u8> = varint_write(data.len() as i32); // packet length
+        frame.append(&mut data);
+
+        Ok(self.stream().write_all(&frame)?)
+    }
+
+    // waits for the next packet, up to READ_TIMEOUT
+    pub fn read_packet(&mut self) -> Result<RawPacket> {
+        loop {
+            if let Some(packet) = self.poll_packet()? {
+                return Ok(packet);
+            }
+        }
+    }
+
+    // reads one whole frame and looks up which packet it is, None when no whole frame
+    // arrived within the read timeout of the stream
+    pub fn poll_packet(&mut self) -> Result<Option<RawPacket>> {
+        let compression_threshold = self.compression_threshold;
+        let frame = match self.frames.read_frame() {
+            Ok(frame) => frame,
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                if self.last_received.elapsed() >= READ_TIMEOUT {
+                    return Err(Error::Timeout);
+                }
+                return Ok(None);
+            }
+            Err(error) => return Err(frame_error(error)),
+        };
+        self.last_received = Instant::now();
+
+        let data = match compression_threshold {
+            Some(threshold) => decompress_packet(frame, threshold).map_err(frame_error)?,
+            None => frame.to_vec(),
+        };
+
+        let mut reader = PacketReader::new(&data);
+        let id = reader.read_varint().map_err(frame_error)?;
+        let header_length = reader.position();
+
+        let kind = self
+            .version
+            .packet_kind(self.state, Direction::Clientbound, id);
+        // the client knows every packet outside of play, an unknown one is out of place
+        if kind.is_none() && self.state != ConnectionState::Play {
+            return Err(Error::protocol(format!(
+                "Unexpected packet {:#04x} in the {:?} state",
+                id, self.state
+            )));
+        }
+
+        Ok(Some(RawPacket {
+            id,
+            kind,
+            protocol: self.version.protocol,
+            data: data[header_length..].to_vec(),
+        }))
+    }
+}
+
+// malformed frames are the server's fault, everything else is the socket's
+fn frame_error(error: std::io::Error) -> Error {
+    match error.kind() {
+        std::io::ErrorKind::InvalidData => Error::Protocol(error.to_string()),
+        _ => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::LATEST;
+    use std::io::Cursor;
+
+    // a connection past login, as the play packets need
+    fn playing<S: Read + Write>(stream: S, version: &'static ProtocolVersion) -> Connection<S> {
+        let mut connection = Connection::new(stream, version);
+        connection.transition(ConnectionState::Login).unwrap();
+        connection.transition(ConnectionState::Play).unwrap();
+        connection
+    }
+
+    fn read_back<P: Packet>(connection: &mut Connection<Cursor<Vec<u8>>>) -> P {
+        let packet = connection.read_packet().unwrap();
+        assert_eq!(LATEST.packet_id(P::KIND), Some(packet.id));
+        packet.decode().unwrap()
+    }
+
+    fn rewind(connection: Connection<Cursor<Vec<u8>>>) -> Connection<Cursor<Vec<u8>>> {
+        let threshold = connection.compression_threshold;
+        let written = connection.frames.into_inner().into_inner().into_inner();
+        let mut connection = playing(Cursor::new(written), LATEST);
+        connection.compression_threshold = threshold;
+        connection
+    }
+
+    #[test]
+    fn test_uncompressed() {
+        let mut connection = playing(Cursor::new(vec![]), LATEST);
+        let message = ServerboundChatMessage {
+            message: "hello".to_string(),
+        };
+        connection.write_packet(&message).unwrap();
+
+        let mut connection = rewind(connection);
+        assert_eq!(
+            message,
+            read_back::<ServerboundChatMessage>(&mut connection)
+        );
+    }
+
+    #[test]
+    fn test_packet_kind() {
+        let mut frames: Vec<u8> = vec![];
+        frames.extend(byte_array_write(&[0x21, 0, 0, 0, 0, 0, 0, 0, 42]));
+        frames.extend(byte_array_write(&[0x7f]));
+
+        let mut connection = playing(Cursor::new(frames), LATEST);
+
+        let packet = connection.read_packet().unwrap();
+        assert_eq!(Some(PacketKind::ClientboundKeepAlive), packet.kind);
+        assert_eq!(
+            ClientboundKeepAlive { keep_alive_id: 42 },
+            packet.decode().unwrap()
+        );
+
+        let packet = connection.read_packet().unwrap();
+        assert_eq!(0x7f, packet.id);
+        assert_eq!(None, packet.kind);
+        assert!(matches!(
+            packet.decode::<ClientboundKeepAlive>(),
+            Err(Error::Decode {
+                packet: PacketKind::ClientboundKeepAlive,
+                ..
+            })
+        ));
+
+        assert!(matches!(connection.read_packet(), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn test_packet_missing_in_version() {
+        let version = crate::version::protocol_version(754).unwrap();
+        let mut connection = playing(Cursor::new(vec![]), version);
+
+        let error = connection.write_packet(&PlayPong { id: 1 }).unwrap_err();
+        assert!(matches!(error, Error::Protocol(_)));
+        assert!(connection
+            .write_packet(&ServerboundKeepAlive { keep_alive_id: 1 })
+            .is_ok());
+    }
+
+    #[test]
+    fn test_compressed() {
+        let mut connection = playing(Cursor::new(vec![]), LATEST);
+        connection.set_compression(64);
+
+        let short = ServerboundChatMessage {
+            message: "hi".to_string(),
+        };
+        let long = ServerboundChatMessage {
+            message: "spam ".repeat(50),
+        };
+        connection.write_packet(&short).unwrap();
+        connection.write_packet(&long).unwrap();
+
+        let mut connection = rewind(connection);
+        for expected in [short, long] {
+            assert_eq!(
+                expected,
+                read_back::<ServerboundChatMessage>(&mut connection)
+            );
+        }
+
+        connection.set_compression(-1);
+        assert_eq!(None, connection.compression_threshold());
+    }
+
+    #[test]
+    fn test_encrypted_and_compressed() {
+        let shared_secret = [7u8; 16];
+        let mut connection = playing(Cursor::new(vec![]), LATEST);
+        connection.set_compression(16);
+        connection.enable_encryption(&shared_secret).unwrap();
+
+        let packets = [
+            ServerboundKeepAlive { keep_alive_id: 1 },
+            ServerboundKeepAlive {
+                keep_alive_id: i64::MAX,
+            },
+        ];
+        for packet in &packets {
+            connection.write_packet(packet).unwrap();
+        }
+
+        let mut connection = rewind(connection);
+        connection.enable_encryption(&shared_secret).unwrap();
+        for expected in packets {
+            assert_eq!(expected, read_back::<ServerboundKeepAlive>(&mut connection));
+        }
+    }
+
+    #[test]
+    fn test_state_machine() {
+        let mut frames: Vec<u8> = vec![];
+        frames.extend(byte_array_write(&[0x02, 0x00])); // Login Success id, not in Status
+        let mut connection = Connection::new(Cursor::new(frames), LATEST);
+        assert_eq!(ConnectionState::Handshaking, connection.state());
+
+        assert!(matches!(
+            connection.transition(ConnectionState::Play),
+            Err(Error::Protocol(_))
+        ));
+        assert!(matches!(
+            connection.write_packet(&LoginStart {
+                username: "Steve".to_string(),
+            }),
+            Err(Error::Protocol(_))
+        ));
+
+        connection.transition(ConnectionState::Status).unwrap();
+        let error = connection.read_packet().unwrap_err();
+        assert_eq!(
+            "Protocol error: Unexpected packet 0x02 in the Status state",
+            error.to_string()
+        );
+        assert!(connection.write_packet(&StatusRequest).is_ok());
+        assert!(connection.transition(ConnectionState::Login).is_err());
+        assert_eq!(ConnectionState::Status, connection.state());
+    }
+}
@@ -0,0 +1,158 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+use mclient::commands::*;
+
+// how long Tab waits for the server to answer a Tab-Complete request
+const SERVER_COMPLETION_TIMEOUT: Duration = Duration::from_secs(1);
+
+// What the console completes locally, kept up to date by the connection
+#[derive(Debug, Default)]
+pub struct CompletionSource {
+    pub commands: Option<CommandGraph>,
+    pub players: Vec<String>,
+}
+
+// The answer of the server to a line sent with a Tab-Complete request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerCompletion {
+    pub text: String,
+    pub start: usize, // byte index in text
+    pub matches: Vec<String>,
+}
+
+// The connection side of the console: the lines typed, and the completions only the server
+// can make (requests in, answers out)
+pub struct Console {
+    pub commands: Arc<Mutex<Vec<String>>>,
+    pub source: Arc<Mutex<CompletionSource>>,
+    pub completion_requests: Receiver<String>,
+    pub completions: Sender<ServerCompletion>,
+}
+
+struct ConsoleHelper {
+    source: Arc<Mutex<CompletionSource>>,
+    completion_requests: Sender<String>,
+    completions: Receiver<ServerCompletion>,
+}
+
+impl ConsoleHelper {
+    fn ask_server(&self, text: &str) -> Option<(usize, Vec<String>)> {
+        // answers that came after an earlier Tab gave up waiting
+        while self.completions.try_recv().is_ok() {}
+        self.completion_requests.send(text.to_string()).ok()?;
+
+        let deadline = Instant::now() + SERVER_COMPLETION_TIMEOUT;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.completions.recv_timeout(timeout) {
+                Ok(completion) if completion.text == text => {
+                    return Some((completion.start, completion.matches))
+                }
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+}
+
+impl Completer for ConsoleHelper {
+    type Candidate = String;
+
+    // commands complete with the graph the server declared, chat with the player names
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _context: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let text = &line[..pos];
+        let completion = {
+            let source = self.source.lock().unwrap();
+            let names: Vec<&str> = source.players.iter().map(String::as_str).collect();
+            match &source.commands {
+                _ if !text.starts_with('/') => complete_chat(text, &names),
+                Some(commands) => commands.complete(text, &names),
+                None => Completion::AskServer,
+            }
+        };
+
+        Ok(match completion {
+            Completion::Local { start, matches } => (start, matches),
+            Completion::AskServer => self.ask_server(text).unwrap_or((pos, vec![])),
+        })
+    }
+}
+
+impl Hinter for ConsoleHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ConsoleHelper {}
+
+impl Validator for ConsoleHelper {}
+
+impl Helper for ConsoleHelper {}
+
+// reads lines on its own thread, Tab completes them
+pub fn spawn() -> Console {
+    let commands = Arc::new(Mutex::new(vec![]));
+    let source = Arc::new(Mutex::new(CompletionSource::default()));
+    let (request_sender, request_receiver) = mpsc::channel();
+    let (completion_sender, completion_receiver) = mpsc::channel();
+
+    let helper = ConsoleHelper {
+        source: source.clone(),
+        completion_requests: request_sender,
+        completions: completion_receiver,
+    };
+    let queue = commands.clone();
+    thread::spawn(move || read_lines(helper, queue));
+
+    Console {
+        commands,
+        source,
+        completion_requests: request_receiver,
+        completions: completion_sender,
+    }
+}
+
+fn read_lines(helper: ConsoleHelper, queue: Arc<Mutex<Vec<String>>>) {
+    let mut editor = match Editor::<ConsoleHelper, DefaultHistory>::new() {
+        Ok(editor) => editor,
+        Err(error) => return eprintln!("Error opening the console: {}", error),
+    };
+    editor.set_helper(Some(helper));
+
+    loop {
+        match editor.readline("") {
+            Ok(line) => {
+                let line = line.trim();
+                if !line.is_empty() {
+                    let _ = editor.add_history_entry(line);
+                    queue.lock().unwrap().push(line.to_string());
+                }
+            }
+            // Ctrl-C no longer reaches the process while the line is edited
+            Err(ReadlineError::Interrupted) => {
+                queue.lock().unwrap().push("quit".to_string());
+                return;
+            }
+            Err(ReadlineError::Eof) => return,
+            Err(error) => {
+                eprintln!("Error reading from console: {}", error);
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::io::{self, Read, Write};
+
+use aes::Aes128;
+use cfb8::cipher::generic_array::GenericArray;
+use cfb8::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{Pkcs1v15Encrypt, RsaPublicKey};
+use sha1::{Digest, Sha1};
+
+use crate::packet::{EncryptionRequest, EncryptionResponse};
+
+type Encryptor = cfb8::Encryptor<Aes128>;
+type Decryptor = cfb8::Decryptor<Aes128>;
+
+// Passes bytes through until enable() is called, then encrypts everything written and
+// decrypts everything read with AES-128-CFB8 (the shared secret is both key and iv)
+pub struct CipherStream<S> {
+    inner: S,
+    encryptor: Option<Encryptor>,
+    decryptor: Option<Decryptor>,
+}
+
+impl<S> CipherStream<S> {
+    pub fn new(inner: S) -> Self {
+        CipherStream {
+            inner,
+            encryptor: None,
+            decryptor: None,
+        }
+    }
+
+    pub fn enable(&mut self, shared_secret: &[u8; 16]) {
+        let key = GenericArray::from_slice(shared_secret);
+        self.encryptor = Some(Encryptor::new(key, key));
+        self.decryptor = Some(Decryptor::new(key, key));
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.encryptor.is_some()
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Read> Read for CipherStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_bytes = self.inner.read(buf)?;
+
+        if let Some(decryptor) = &mut self.decryptor {
+            for byte in buf[..read_bytes].chunks_mut(1) {
+                decryptor.decrypt_block_mut(GenericArray::from_mut_slice(byte));
+            }
+        }
+
+        Ok(read_bytes)
+    }
+}
+
+impl<S: Write> Write for CipherStream<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encryptor {
+            Some(encryptor) => {
+                // the cipher state moves forward with every byte, so everything must be written
+                let mut encrypted = buf.to_vec();
+                for byte in encrypted.chunks_mut(1) {
+                    encryptor.encrypt_block_mut(GenericArray::from_mut_slice(byte));
+                }
+                self.inner.write_all(&encrypted)?;
+                Ok(buf.len())
+            }
+            None => self.inner.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub fn generate_shared_secret() -> [u8; 16] {
+    let mut shared_secret = [0u8; 16];
+    OsRng.fill_bytes(&mut shared_secret);
+    shared_secret
+}
+
+// sha1(server id + shared secret + public key) printed as a signed big integer in hex,
+// the way the session server expects it
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(server_id.as_bytes());
+    hasher.update(shared_secret);
+    hasher.update(public_key);
+    let mut digest: [u8; 20] = hasher.finalize().into();
+
+    let negative = digest[0] & 0x80 != 0;
+    if negative {
+        // two's complement
+        let mut carry = true;
+        for byte in digest.iter_mut().rev() {
+            *byte = !*byte;
+            if carry {
+                let (value, overflow) = byte.overflowing_add(1);
+                *byte = value;
+                carry = overflow;
+            }
+        }
+    }
+
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let hex = hex.trim_start_matches('0');
+
+    if negative {
+        format!("-{}", hex)
+    } else {
+        hex.to_string()
+    }
+}
+
+pub fn encryption_response(
+    request: &EncryptionRequest,
+    shared_secret: &[u8; 16],
+) -> io::Result<EncryptionResponse> {
+    let public_key = RsaPublicKey::from_public_key_der(&request.public_key)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    let mut rng = OsRng;
+    let mut encrypt = |data: &[u8]| {
+        public_key
+            .encrypt(&mut rng, Pkcs1v15Encrypt, data)
+            .map_err(io::Error::other)
+    };
+
+    Ok(EncryptionResponse {
+        shared_secret: encrypt(shared_secret)?,
+        verify_token: encrypt(&request.verify_token)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pkcs8::EncodePublicKey;
+    use rsa::RsaPrivateKey;
+
+    fn sha1_hex(name: &str) -> String {
+        server_hash(name, &[], &[])
+    }
+
+    #[test]
+    fn test_server_hash() {
+        assert_eq!(
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48",
+            sha1_hex("Notch")
+        );
+        assert_eq!(
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1",
+            sha1_hex("jeb_")
+        );
+        assert_eq!("88e16a1019277b15d58faf0541e11910eb756f6", sha1_hex("simon"));
+    }
+
+    #[test]
+    fn test_cipher_stream() {
+        let shared_secret = generate_shared_secret();
+
+        let mut writer = CipherStream::new(vec![]);
+        writer.write_all(b"plain").unwrap();
+        writer.enable(&shared_secret);
+        assert!(writer.is_enabled());
+        writer.write_all(b"hello ").unwrap();
+        writer.write_all(b"server").unwrap();
+
+        let written = writer.into_inner();
+        assert_eq!(b"plain", &written[..5]);
+        assert_ne!(b"hello server", &written[5..]);
+
+        let mut reader = CipherStream::new(&written[5..]);
+        reader.enable(&shared_secret);
+        let mut decrypted = String::new();
+        reader.read_to_string(&mut decrypted).unwrap();
+        assert_eq!("hello server", decrypted);
+    }
+
+    #[test]
+    fn test_encryption_response() {
+        let private_key = RsaPrivateKey::new(&mut OsRng, 1024).unwrap();
+        let request = EncryptionRequest {
+            server_id: "".to_string(),
+            public_key: private_key
+                .to_public_key()
+                .to_public_key_der()
+                .unwrap()
+                .into_vec(),
+            verify_token: vec![9, 8, 7, 6],
+        };
+        let shared_secret = generate_shared_secret();
+
+        let response = encryption_response(&request, &shared_secret).unwrap();
+
+        assert_eq!(
+            shared_secret.to_vec(),
+            private_key
+                .decrypt(Pkcs1v15Encrypt, &response.shared_secret)
+                .unwrap()
+        );
+        assert_eq!(
+            request.verify_token,
+            private_key
+                .decrypt(Pkcs1v15Encrypt, &response.verify_token)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt;
+use std::io;
+
+use crate::chat::{ChatComponent, Content};
+use crate::translation::Translations;
+use crate::version::PacketKind;
+
+// Everything that can end a connection to the server
+#[derive(Debug)]
+pub enum Error {
+    // the socket or a file failed
+    Io(io::Error),
+    // the server sent something the client cannot follow: a bad frame, a packet out of
+    // place, a protocol version the client does not speak
+    Protocol(String),
+    // the fields of a known packet are malformed
+    Decode {
+        packet: PacketKind,
+        source: io::Error,
+    },
+    // a packet built by the client cannot be written, e.g. a malformed NBT tag
+    Encode {
+        packet: PacketKind,
+        source: io::Error,
+    },
+    // the server closed the connection with a Disconnect or Login Disconnect packet
+    Disconnected {
+        reason: Box<ChatComponent>,
+    },
+    // nothing was received for longer than the read timeout
+    Timeout,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn protocol(message: impl Into<String>) -> Error {
+        Error::Protocol(message.into())
+    }
+
+    // like Display, with the disconnect reason in the language the user loaded
+    pub fn describe(&self, translations: &Translations) -> String {
+        match self {
+            Error::Disconnected { reason } => {
+                format!(
+                    "Disconnected by the server: {}",
+                    reason.to_plain(translations)
+                )
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    // whether connecting again can help
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Error::Io(_) | Error::Timeout)
+    }
+
+    // the process exit code, so scripts can tell why the client stopped
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            // 1 is a bad language file, 2 is what clap exits with for bad arguments
+            Error::Io(_) => 3,
+            Error::Protocol(_) | Error::Decode { .. } | Error::Encode { .. } => 4,
+            Error::Timeout => 5,
+            Error::Disconnected { reason } => DisconnectCategory::of(reason).exit_code(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectCategory {
+    Kicked,
+    Banned,
+    NotWhitelisted,
+    ServerFull,
+    OutdatedVersion,
+    ServerClosed,
+    Authentication,
+    Idle,
+    // plugin kicks and anything else sent as plain text
+    Other,
+}
+
+impl DisconnectCategory {
+    // vanilla servers send the reason as a translate component, the key tells the category
+    pub fn of(reason: &ChatComponent) -> DisconnectCategory {
+        let key = match translate_key(reason) {
+            Some(key) => key,
+            None => return DisconnectCategory::Other,
+        };
+
+        match key.strip_prefix("multiplayer.disconnect.").unwrap_or(key) {
+            "kicked" => DisconnectCategory::Kicked,
+            key if key.starts_with("banned") => DisconnectCategory::Banned,
+            "not_whitelisted" => DisconnectCategory::NotWhitelisted,
+            "server_full" => DisconnectCategory::ServerFull,
+            "outdated_client" | "outdated_server" | "incompatible" => {
+                DisconnectCategory::OutdatedVersion
+            }
+            "server_shutdown" => DisconnectCategory::ServerClosed,
+            "unverified_username" | "authservers_down" | "duplicate_login" => {
+                DisconnectCategory::Authentication
+            }
+            "idling" => DisconnectCategory::Idle,
+            _ => DisconnectCategory::Other,
+        }
+    }
+
+    pub fn exit_code(self) -> u8 {
+        match self {
+            DisconnectCategory::Other => 10,
+            DisconnectCategory::Kicked => 11,
+            DisconnectCategory::Banned => 12,
+            DisconnectCategory::NotWhitelisted => 13,
+            DisconnectCategory::ServerFull => 14,
+            DisconnectCategory::OutdatedVersion => 15,
+            DisconnectCategory::ServerClosed => 16,
+            DisconnectCategory::Authentication => 17,
+            DisconnectCategory::Idle => 18,
+        }
+    }
+}
+
+// the key of the component, or of the first translated child when the root is empty text
+fn translate_key(component: &ChatComponent) -> Option<&str> {
+    match &component.content {
+        Content::Translate { key, .. } => Some(key),
+        Content::Text(text) if text.is_empty() => component.extra.iter().find_map(translate_key),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+            Error::Protocol(message) => write!(f, "Protocol error: {}", message),
+            Error::Decode { packet, source } => {
+                write!(f, "Could not decode {:?}: {}", packet, source)
+            }
+            Error::Encode { packet, source } => {
+                write!(f, "Could not encode {:?}: {}", packet, source)
+            }
+            Error::Disconnected { reason } => write!(
+                f,
+                "Disconnected by the server: {}",
+                reason.to_plain(Translations::bundled())
+            ),
+            Error::Timeout => write!(f, "Timed out waiting for the server"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error)
+            | Error::Decode { source: error, .. }
+            | Error::Encode { source: error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            // what a socket read timeout looks like, depending on the platform
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Error::Timeout,
+            _ => Error::Io(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_io() {
+        let timeout: Error = io::Error::from(io::ErrorKind::WouldBlock).into();
+        assert!(matches!(timeout, Error::Timeout));
+        assert!(timeout.is_transient());
+
+        let refused: Error = io::Error::from(io::ErrorKind::ConnectionRefused).into();
+        assert!(matches!(refused, Error::Io(_)));
+
+        let kicked = Error::Disconnected {
+            reason: Box::new(ChatComponent::text("Server closed")),
+        };
+        assert!(!kicked.is_transient());
+        assert!(!Error::protocol("bad frame").is_transient());
+        assert_eq!(10, kicked.exit_code());
+        assert_eq!(
+            "Disconnected by the server: Server closed",
+            kicked.describe(Translations::bundled())
+        );
+        assert_eq!(5, timeout.exit_code());
+    }
+
+    #[test]
+    fn test_disconnect_category() {
+        let category =
+            |json: &str| DisconnectCategory::of(&ChatComponent::from_json(json).unwrap());
+
+        assert_eq!(
+            DisconnectCategory::Banned,
+            category(r#"{"translate":"multiplayer.disconnect.banned.reason","with":["Griefing"]}"#)
+        );
+        assert_eq!(
+            DisconnectCategory::NotWhitelisted,
+            category(r#"{"translate":"multiplayer.disconnect.not_whitelisted"}"#)
+        );
+        assert_eq!(
+            DisconnectCategory::ServerClosed,
+            category(
+                r#"{"text":"","extra":[{"translate":"multiplayer.disconnect.server_shutdown"}]}"#
+            )
+        );
+        assert_eq!(
+            DisconnectCategory::Other,
+            category(r#"{"text":"You were kicked by a plugin"}"#)
+        );
+
+        let codes: std::collections::HashSet<u8> = [
+            DisconnectCategory::Kicked,
+            DisconnectCategory::Banned,
+            DisconnectCategory::NotWhitelisted,
+            DisconnectCategory::ServerFull,
+            DisconnectCategory::OutdatedVersion,
+            DisconnectCategory::ServerClosed,
+            DisconnectCategory::Authentication,
+            DisconnectCategory::Idle,
+            DisconnectCategory::Other,
+        ]
+        .iter()
+        .map(|category| category.exit_code())
+        .collect();
+        assert_eq!(9, codes.len());
+    }
+}
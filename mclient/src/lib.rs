@@ -0,0 +1,19 @@
+pub mod auth;
+pub mod boss_bars;
+pub mod chat;
+pub mod commands;
+pub mod compression;
+pub mod connection;
+pub mod encryption;
+pub mod error;
+pub mod nbt;
+pub mod packet;
+pub mod players;
+pub mod plugin;
+pub mod reader;
+pub mod scoreboard;
+pub mod session;
+pub mod titles;
+pub mod translation;
+pub mod varint_type;
+pub mod version;
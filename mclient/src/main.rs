@@ -1,210 +1,80 @@
-use anyhow::Result;
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
-use std::collections::HashMap;
 use std::fs::File;
-use std::io::Read;
 use std::io::{self, Write};
-use std::sync::{Arc, Mutex};
+use std::process::ExitCode;
 use std::thread;
-
-use serde_derive::{Deserialize, Serialize};
-use serde_json::Value;
-
-mod varint_type;
-use varint_type::*;
-
-const CONTINUE_BIT: i32 = 0x80; // for packets in loop to allow writing in the command line
-
-use std::net::TcpStream;
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ChatComponent {
-    insertion: Option<String>,
-    text: Option<String>,
-    color: Option<String>,
-    bold: Option<bool>,
-    italic: Option<bool>,
-    underlined: Option<bool>,
-    strikethrough: Option<bool>,
-    obfuscated: Option<bool>,
-    font: Option<String>,
-}
-
-fn get_chat_message(json_string: &str) {
-    let mut formatted_text = String::new();
-
-    if let Ok(json) = serde_json::from_str::<Value>(json_string) {
-        if let Some(bold) = json.get("bold").and_then(|b| b.as_bool()) {
-            if bold {
-                formatted_text.push_str("\x1B[1m");
-            }
-        }
-        if let Some(italic) = json.get("italic").and_then(|i| i.as_bool()) {
-            if italic {
-                formatted_text.push_str("\x1B[3m");
-            }
-        }
-
-        if let Some(underlined) = json.get("underlined").and_then(|u| u.as_bool()) {
-            if underlined {
-                formatted_text.push_str("\x1B[4m");
-            }
-        }
-
-        if let Some(strikethrough) = json.get("strikethrough").and_then(|s| s.as_bool()) {
-            if strikethrough {
-                formatted_text.push_str("\x1B[9m");
-            }
-        }
-
-        if let Some(color) = json.get("color").and_then(|c| c.as_str()) {
-            match color {
-                "black" => formatted_text.push_str("\x1B[30m"),
-                "dark_blue" => formatted_text.push_str("\x1B[34m"),
-                "dark_green" => formatted_text.push_str("\x1B[32m"),
-                "dark_aqua" => formatted_text.push_str("\x1B[36m"),
-                "dark_red" => formatted_text.push_str("\x1B[31m"),
-                "dark_purple" => formatted_text.push_str("\x1B[35m"),
-                "gold" => formatted_text.push_str("\x1B[33m"),
-                "gray" => formatted_text.push_str("\x1B[37m"),
-                "dark_gray" => formatted_text.push_str("\x1B[90m"),
-                "blue" => formatted_text.push_str("\x1B[94m"),
-                "green" => formatted_text.push_str("\x1B[92m"),
-                "aqua" => formatted_text.push_str("\x1B[96m"),
-                "red" => formatted_text.push_str("\x1B[91m"),
-                "light_purple" => formatted_text.push_str("\x1B[95m"),
-                "yellow" => formatted_text.push_str("\x1B[93m"),
-                "white" => formatted_text.push_str("\x1B[97m"),
-                _ => {}
-            }
-        }
-
-        let translate = json
-            .get("translate")
-            .and_then(|t| t.as_str())
-            .unwrap_or_default();
-
-        match translate {
-            "chat.type.text" | "commands.message.display.incoming" => {
-                if let Some(with) = json.get("with").and_then(|w| w.as_array()) {
-                    if with.len() == 2 {
-                        if let Some(insertion) = with[0].get("insertion").and_then(|i| i.as_str()) {
-                            formatted_text.push_str(&format!("<{}> ", insertion));
-                        }
-
-                        if let Some(text) = with[1].as_str() {
-                            formatted_text.push_str(text);
-                        } else if let Some(text_obj) = with[1].get("text").and_then(|t| t.as_str())
-                        {
-                            formatted_text.push_str(text_obj);
-                        }
-                    }
-                }
-            }
-            "multiplayer.player.left" => {
-                if let Some(with) = json.get("with").and_then(|w| w.as_array()) {
-                    if with.len() == 1 {
-                        if let Some(text) = with[0].as_str() {
-                            formatted_text.push_str(&format!("{} left the game", text));
-                        } else if let Some(text_obj) = with[0].get("text").and_then(|t| t.as_str())
-                        {
-                            formatted_text.push_str(&format!("{} left the game", text_obj));
-                        }
-                    }
-                }
-            }
-            "multiplayer.player.joined" => {
-                if let Some(with) = json.get("with").and_then(|w| w.as_array()) {
-                    if with.len() == 1 {
-                        if let Some(text) = with[0].as_str() {
-                            formatted_text.push_str(&format!("{} joined the game", text));
-                        } else if let Some(text_obj) = with[0].get("text").and_then(|t| t.as_str())
-                        {
-                            formatted_text.push_str(&format!("{} joined the game", text_obj));
-                        }
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-
-    println!("{}\x1B[0m", formatted_text);
-}
-
-fn connect_to_server() -> Result<TcpStream, io::Error> {
-    let stream: TcpStream = TcpStream::connect("127.0.0.1:25565")?;
-    Ok(stream)
+use std::time::{Duration, Instant};
+
+mod cli;
+mod console;
+use cli::{Args, Mode};
+use console::{CompletionSource, Console, ServerCompletion};
+use mclient::auth::SessionAuthenticator;
+use mclient::boss_bars::BossBars;
+use mclient::chat::ChatComponent;
+use mclient::commands::*;
+use mclient::connection::{Connection, RawPacket};
+use mclient::encryption::*;
+use mclient::error::{Error, Result};
+use mclient::packet::*;
+use mclient::players::*;
+use mclient::plugin::*;
+use mclient::scoreboard::Scoreboard;
+use mclient::session::*;
+use mclient::titles::*;
+use mclient::translation::Translations;
+use mclient::version::*;
+
+use clap::Parser;
+
+fn connect_to_server(args: &Args, version: &'static ProtocolVersion) -> Result<Connection> {
+    Connection::connect(&args.address(), version)
 }
 
-fn console_reader(shared_command_queue: Arc<Mutex<Vec<String>>>) {
-    loop {
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                let trimmed = input.trim().to_string();
-                if !trimmed.is_empty() {
-                    let mut command_queue = shared_command_queue.lock().unwrap();
-                    command_queue.push(trimmed);
-                }
-            }
-            Err(error) => eprintln!("Error reading from console: {:?}", error),
-        }
-    }
-}
-
-fn handshake(stream: &mut TcpStream, state: i32) -> io::Result<()> {
-    let mut send_handshake: Vec<u8> = vec![];
-    send_handshake.append(&mut varint_write(0i32)); // id
-    let mut protocol_version: Vec<u8> = varint_write(758); // protocol version
-    send_handshake.append(&mut protocol_version);
-    let address: String = "127.0.0.1".to_string();
-    send_handshake.append(&mut varint_write(address.len() as i32)); //addres length
-    send_handshake.append(&mut address.as_bytes().to_vec());
-    let port: u16 = 25565;
-    send_handshake.append(&mut port.to_be_bytes().to_vec());
-    let mut next_state: Vec<u8> = varint_write(state); // 1 for status request,
-    send_handshake.append(&mut next_state);
-
-    let mut packet_length: Vec<u8> = varint_write(send_handshake.len() as i32);
-
-    packet_length.append(&mut send_handshake);
-
-    stream.write_all(&packet_length)?;
-
-    Ok(())
+fn handshake(
+    connection: &mut Connection,
+    args: &Args,
+    protocol_version: i32,
+    next_state: ConnectionState,
+) -> Result<()> {
+    let handshake = Handshake {
+        protocol_version,
+        server_address: args.host.clone(),
+        server_port: args.port,
+        next_state: next_state.handshake_id().ok_or_else(|| {
+            Error::protocol(format!("Cannot handshake into the {:?} state", next_state))
+        })?,
+    };
+
+    connection.write_packet(&handshake)?;
+    connection.transition(next_state)
 }
 
-fn print_status_and_save_favicon(buf: &mut Vec<u8>) -> io::Result<()> {
-    let json_length = varint_read(buf).unwrap() as usize; // json length
-
-    let json_data = String::from_utf8(buf.drain(..json_length).collect()).unwrap();
-
+fn print_status_and_save_favicon(json_data: &str) -> Result<()> {
     println!("Server status: {}", json_data);
 
     // Writing JSON data to file
     let mut f = File::create("status_response.json")?;
-    f.write_all(json_data.as_bytes()).unwrap();
-
-    // saving the favicon
-
-    let pattern = "\"favicon\":\"data:image/png;base64,".to_string();
+    f.write_all(json_data.as_bytes())?;
 
-    let pattern_length = pattern.len();
+    // saving the favicon, servers without an icon leave it out
 
-    let start_index = json_data
-        .find("\"favicon\":\"data:image/png;base64,")
-        .unwrap()
-        + pattern_length;
+    let status: serde_json::Value = serde_json::from_str(json_data)
+        .map_err(|error| Error::protocol(format!("Invalid status response: {}", error)))?;
 
-    let remaining_data = &json_data[start_index..];
+    let Some(favicon) = status["favicon"].as_str() else {
+        println!("The server has no icon");
+        return Ok(());
+    };
 
-    let end_index = remaining_data.find('\"').unwrap();
+    let favicon_data = favicon
+        .strip_prefix("data:image/png;base64,")
+        .ok_or_else(|| Error::protocol("The server icon is not a base64 png"))?;
 
-    let favicon_data = &remaining_data[..end_index];
-
-    let decoded_data = STANDARD.decode(favicon_data).unwrap();
+    let decoded_data = STANDARD
+        .decode(favicon_data)
+        .map_err(|error| Error::protocol(format!("Invalid server icon: {}", error)))?;
 
     let mut png_file = File::create("server-icon.png")?;
     png_file.write_all(&decoded_data)?;
@@ -216,432 +86,982 @@ fn print_status_and_save_favicon(buf: &mut Vec<u8>) -> io::Result<()> {
 
 fn help_command() {
     println!("Commands:");
-    println!("list: shows the online players");
+    println!("list [--sort name|ping] [--json]: shows the online players as a table or as json");
+    println!("tab: shows the tab list with its header and footer");
+    println!("scoreboard: shows the sidebar objective and its scores");
+    println!("bossbars: shows the boss bars with their titles");
+    println!("info: shows the game mode, dimension and difficulty of the player");
+    println!("pos: shows the position of the player");
+    println!("respawn: respawns the player after death");
     println!("status: prints the server status and downloads the server icon");
     println!("help: shows the commands");
     println!("quit: disconnects from the server");
     println!("any other commands: sends a chat message to the server with the string");
+    println!("Tab completes commands, their arguments and player names");
 }
 
-fn request_status(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
-    // Send the Status Request packet (Packet ID: 0x00)
+fn expect_packet<P: Packet>(connection: &mut Connection) -> Result<P> {
+    let packet = connection.read_packet()?;
 
-    let mut send_request_status: Vec<u8> = vec![];
+    if packet.kind != Some(P::KIND) {
+        return Err(Error::protocol(format!(
+            "Expected {:?}, received packet {:#04x}",
+            P::KIND,
+            packet.id
+        )));
+    }
 
-    send_request_status.append(&mut varint_write(0i32)); // packet id
+    packet.decode()
+}
 
-    let mut packet_length: Vec<u8> = varint_write(send_request_status.len() as i32); // packet length
+// status handshake, request and ping on a separate connection, returns the status json
+fn server_status(args: &Args) -> Result<String> {
+    let mut status_connection = connect_to_server(args, LATEST)?;
+
+    let protocol = args.protocol_version.unwrap_or(DETECT_PROTOCOL);
+    handshake(
+        &mut status_connection,
+        args,
+        protocol,
+        ConnectionState::Status,
+    )?;
+    let response_status = request_status(&mut status_connection)?;
+    ping_request(&mut status_connection)?;
+    ping_response(&mut status_connection)?;
+
+    Ok(response_status)
+}
 
-    packet_length.append(&mut send_request_status); // prefixed by packet length
+fn request_status(connection: &mut Connection) -> Result<String> {
+    connection.write_packet(&StatusRequest)?;
 
-    stream.write_all(&packet_length)?; // sending to player
+    Ok(expect_packet::<StatusResponse>(connection)?.json)
+}
 
-    // --------reading status response--------
-    // format:
-    //   length
-    //   id
-    //   length of json
-    //   json
-    let buffer_len: usize = 32767;
-    let mut buf = vec![0u8; 32767];
-    let read_bytes = stream.read(&mut buf).unwrap();
+fn login_start(connection: &mut Connection, username: &str) -> Result<()> {
+    let login_start = LoginStart {
+        username: username.to_string(),
+    };
 
-    if read_bytes <= buffer_len {
-        // Extracting length, id, and json length
-        let _length = varint_read(&mut buf).unwrap();
+    connection.write_packet(&login_start)
+}
 
-        let _id = varint_read(&mut buf).unwrap();
+fn login_succes(
+    connection: &mut Connection,
+    authenticator: &dyn SessionAuthenticator,
+    login_plugin_channels: &mut LoginPluginChannels,
+) -> Result<()> {
+    loop {
+        let packet = connection.read_packet()?;
+
+        match packet.kind {
+            Some(PacketKind::EncryptionRequest) => {
+                let encryption_request: EncryptionRequest = packet.decode()?;
+                let shared_secret = generate_shared_secret();
+
+                authenticator.join(&server_hash(
+                    &encryption_request.server_id,
+                    &shared_secret,
+                    &encryption_request.public_key,
+                ))?;
+
+                let response = encryption_response(&encryption_request, &shared_secret)?;
+                connection.write_packet(&response)?;
+                connection.enable_encryption(&shared_secret)?;
+            }
+            Some(PacketKind::SetCompression) => {
+                let set_compression: SetCompression = packet.decode()?;
+                connection.set_compression(set_compression.threshold);
+            }
+            Some(PacketKind::LoginPluginRequest) => {
+                let request: LoginPluginRequest = packet.decode()?;
+                let response = login_plugin_channels.respond(&request);
+                println!(
+                    "\x1B[2mLogin plugin request on {} ({})\x1B[0m",
+                    request.channel,
+                    if response.data.is_some() {
+                        "answered"
+                    } else {
+                        "not understood"
+                    }
+                );
+                connection.write_packet(&response)?;
+            }
+            Some(PacketKind::LoginDisconnect) => {
+                let login_disconnect: LoginDisconnect = packet.decode()?;
+                return Err(Error::Disconnected {
+                    reason: Box::new(ChatComponent::from_json_lossy(&login_disconnect.reason)),
+                });
+            }
+            Some(PacketKind::LoginSuccess) => {
+                let login_success: LoginSuccess = packet.decode()?;
 
-        Ok(buf)
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Error reading from stream",
-        ))
+                println!(
+                    "User connected with username: {} and uuid: {}",
+                    login_success.username, login_success.uuid
+                );
+                connection.transition(ConnectionState::Play)?;
+
+                return Ok(());
+            }
+            _ => {
+                return Err(Error::protocol(format!(
+                    "Unexpected packet {:#04x} during login",
+                    packet.id
+                )))
+            }
+        }
     }
 }
 
-fn login_start(stream: &mut TcpStream) -> io::Result<()> {
-    // println!("Sending Login Start packet...");
+const PING_PAYLOAD: i64 = 92233720;
 
-    let mut login_start_packet: Vec<u8> = vec![];
+fn ping_request(connection: &mut Connection) -> Result<()> {
+    connection.write_packet(&PingRequest {
+        payload: PING_PAYLOAD,
+    })
+}
 
-    login_start_packet.append(&mut varint_write(0i32)); //id 0x00
+fn ping_response(connection: &mut Connection) -> Result<()> {
+    let ping_response = expect_packet::<PingResponse>(connection)?;
 
-    let username: String = "eudinaltapartee".to_string(); // username
+    if ping_response.payload == PING_PAYLOAD {
+        Ok(())
+    } else {
+        Err(Error::protocol("Error at ping - pong"))
+    }
+}
 
-    login_start_packet.append(&mut varint_write(username.len() as i32)); // username length
+fn keep_alive(connection: &mut Connection, packet: &RawPacket) -> Result<()> {
+    let keep_alive: ClientboundKeepAlive = packet.decode()?;
 
-    login_start_packet.append(&mut username.as_bytes().to_vec()); // sending player username
+    connection.write_packet(&ServerboundKeepAlive {
+        keep_alive_id: keep_alive.keep_alive_id,
+    })
+}
 
-    let mut packet_length: Vec<u8> = varint_write(login_start_packet.len() as i32);
+fn pong(connection: &mut Connection, packet: &RawPacket) -> Result<()> {
+    println!("Sending Pong...");
 
-    packet_length.append(&mut login_start_packet);
+    let ping: PlayPing = packet.decode()?;
 
-    stream.write_all(&packet_length).unwrap();
+    connection.write_packet(&PlayPong { id: ping.id })?;
 
-    // println!("Login Start packet sent.");
+    println!("Pong packet sent.");
 
     Ok(())
 }
 
-fn login_succes(stream: &mut TcpStream) -> io::Result<()> {
-    let buf_len: usize = 700;
-    let mut buf = vec![0u8; 700];
-    let bytes_read = stream.read(&mut buf).unwrap();
-
-    if bytes_read <= buf_len {
-        // length
-        // id
-        // uuid
-        // user length
-        // number of properties?
-        // property?
-
-        varint_read(&mut buf).unwrap(); // reading packet length
+// five bars like the vanilla tab list, green for a good connection down to red
+fn ping_bars(ping: i32) -> String {
+    let bars = latency_bars(ping) as usize;
+    let color = match bars {
+        4.. => "\x1B[92m",
+        3 => "\x1B[93m",
+        1 | 2 => "\x1B[91m",
+        _ => "\x1B[90m",
+    };
+    format!(
+        "{}{}{}\x1B[0m",
+        color,
+        "\u{2588}".repeat(bars),
+        "\u{2591}".repeat(5 - bars)
+    )
+}
 
-        varint_read(&mut buf).unwrap(); // id
-        let uuid: u128 =
-            u128::from_be_bytes(buf.drain(..16).collect::<Vec<u8>>().try_into().unwrap());
-        let username_length = varint_read(&mut buf).unwrap() as usize;
+fn list_command(
+    options: &[&str],
+    players: &PlayerList,
+    scoreboard: &Scoreboard,
+    translations: &Translations,
+) {
+    let mut order = SortOrder::Name;
+    let mut json = false;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "--json" => json = true,
+            "--sort" => match options.next().and_then(|name| SortOrder::parse(name)) {
+                Some(sort) => order = sort,
+                None => {
+                    println!("--sort takes name or ping");
+                    return;
+                }
+            },
+            option => {
+                println!(
+                    "Unknown list option {}, usage: list [--sort name|ping] [--json]",
+                    option
+                );
+                return;
+            }
+        }
+    }
 
-        let username =
-            String::from_utf8(buf.drain(..username_length).collect::<Vec<u8>>()).unwrap();
+    let entries = players.sorted(order);
+    let game_mode =
+        |entry: &PlayerEntry| GameMode::from_id(entry.game_mode).map_or("unknown", GameMode::name);
+    let display_name = |entry: &PlayerEntry| {
+        entry
+            .display_name
+            .as_deref()
+            .map(ChatComponent::from_json_lossy)
+    };
+
+    if json {
+        let players: Vec<serde_json::Value> = entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "name": entry.name,
+                    "uuid": hyphenated_uuid(entry.uuid),
+                    "game_mode": game_mode(entry),
+                    "ping": entry.ping,
+                    "display_name": display_name(entry).map(|name| name.to_plain(translations)),
+                    "team": scoreboard.team_of(&entry.name).map(|team| &team.name),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::Value::Array(players));
+        return;
+    }
 
+    println!("Online players: {}", entries.len());
+    let name_width = entries
+        .iter()
+        .map(|entry| entry.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Name".len());
+    println!(
+        "{:<name_width$}  {:<36}  {:<9}  {:<14}  Display name",
+        "Name", "UUID", "Game mode", "Ping"
+    );
+    for entry in entries {
         println!(
-            "User connected with username: {} and uuid: {}",
-            username, uuid
+            "{:<name_width$}  {}  {:<9}  {} {:>5} ms  {}",
+            entry.name,
+            hyphenated_uuid(entry.uuid),
+            game_mode(entry),
+            ping_bars(entry.ping),
+            entry.ping,
+            tab_name(entry, scoreboard).to_ansi(translations)
         );
-
-        Ok(())
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Error reading from stream",
-        ))
     }
 }
 
-fn ping_request(stream: &mut TcpStream) -> io::Result<()> {
-    //println!("Sending Ping Request packet...");
-
-    let mut ping_request_packet: Vec<u8> = vec![];
-
-    ping_request_packet.append(&mut varint_write(1i32)); //id 0x01
+// the vanilla tab list fills columns of at most 20 players
+const TAB_LIST_ROWS: usize = 20;
+
+// the tab list as the vanilla client draws it: header, players in columns with their ping,
+// footer, all centered
+fn print_tab_list(players: &PlayerList, scoreboard: &Scoreboard, translations: &Translations) {
+    let cells: Vec<(String, usize, i32)> = players
+        .tab_order(scoreboard)
+        .into_iter()
+        .map(|entry| {
+            let name = tab_name(entry, scoreboard);
+            let width = name.to_plain(translations).chars().count();
+            (name.to_ansi(translations), width, entry.ping)
+        })
+        .collect();
+
+    let columns = cells.len().div_ceil(TAB_LIST_ROWS).max(1);
+    let rows = cells.len().div_ceil(columns);
+    let name_width = cells.iter().map(|(_, width, _)| *width).max().unwrap_or(0);
+    // name, a space, the five ping bars, two spaces between columns
+    let cell_width = name_width + 6;
+    let mut lines: Vec<(String, usize)> = (0..rows)
+        .map(|row| {
+            let mut line = String::new();
+            for (column, (name, width, ping)) in cells.iter().skip(row).step_by(rows).enumerate() {
+                if column > 0 {
+                    line.push_str("  ");
+                }
+                line.push_str(name);
+                line.push_str(&" ".repeat(name_width - width + 1));
+                line.push_str(&ping_bars(*ping));
+            }
+            (line, columns * (cell_width + 2) - 2)
+        })
+        .collect();
+
+    let text_lines = |component: Option<&ChatComponent>| -> Vec<(String, usize)> {
+        let Some(component) = component else {
+            return vec![];
+        };
+        component
+            .to_ansi(translations)
+            .split('\n')
+            .zip(component.to_plain(translations).split('\n'))
+            .map(|(ansi, plain)| (format!("{}\x1B[0m", ansi), plain.chars().count()))
+            .collect()
+    };
+    let mut all = text_lines(players.header());
+    all.append(&mut lines);
+    all.append(&mut text_lines(players.footer()));
+
+    let width = all.iter().map(|(_, width)| *width).max().unwrap_or(0);
+    for (line, line_width) in all {
+        println!("{}{}", " ".repeat((width - line_width) / 2), line);
+    }
+}
 
-    let payload: u64 = 92233720u64;
+const BOSS_BAR_WIDTH: usize = 40;
 
-    ping_request_packet.append(&mut payload.to_be_bytes().to_vec());
+fn print_boss_bars(boss_bars: &BossBars, translations: &Translations) {
+    if boss_bars.is_empty() {
+        println!("No boss bars");
+        return;
+    }
 
-    let mut packet_length: Vec<u8> = varint_write(ping_request_packet.len() as i32);
+    for bar in boss_bars.iter() {
+        println!("{}\x1B[0m", bar.title.to_ansi(translations));
+        println!(
+            "{} {:>3.0}%",
+            bar.to_ansi(BOSS_BAR_WIDTH),
+            bar.health.clamp(0.0, 1.0) * 100.0
+        );
+    }
+}
 
-    packet_length.append(&mut ping_request_packet);
+// the sidebar as the vanilla client draws it: the objective name over the lines of
+// team formatted names with their score in red on the right
+fn print_sidebar(scoreboard: &Scoreboard, username: &str, translations: &Translations) {
+    let Some(objective) = scoreboard.sidebar_objective(username) else {
+        println!("No objective is shown in the sidebar");
+        return;
+    };
+
+    let lines: Vec<(String, usize, String)> = scoreboard
+        .sidebar_lines(objective)
+        .into_iter()
+        .map(|(entity, score)| {
+            let name = scoreboard.format_name(entity);
+            let width = name.to_plain(translations).chars().count();
+            (name.to_ansi(translations), width, score.to_string())
+        })
+        .collect();
+    let title_width = objective
+        .display_name
+        .to_plain(translations)
+        .chars()
+        .count();
+    let width = lines
+        .iter()
+        .map(|(_, width, score)| width + 1 + score.len())
+        .max()
+        .unwrap_or(0)
+        .max(title_width);
+
+    println!(
+        "{}{}\x1B[0m",
+        " ".repeat((width - title_width) / 2),
+        objective.display_name.to_ansi(translations)
+    );
+    for (name, name_width, score) in lines {
+        println!(
+            "{}\x1B[0m{}\x1B[91m{}\x1B[0m",
+            name,
+            " ".repeat(width - name_width - score.len()),
+            score
+        );
+    }
+}
 
-    stream.write_all(&packet_length).unwrap();
+fn player_info(packet: &RawPacket, players: &mut PlayerList) -> Result<()> {
+    players.apply(packet.decode::<PlayerInfo>()?.action);
 
     Ok(())
 }
 
-fn ping_response(stream: &mut TcpStream) -> io::Result<()> {
-    let buf_len: usize = 700;
-    let mut buf = vec![0u8; 700];
-    let bytes_read = stream.read(&mut buf).unwrap();
+// confirms the teleport and tells the server the client is at the new location
+fn teleport(connection: &mut Connection, packet: &RawPacket, session: &mut Session) -> Result<()> {
+    let teleport: PlayerPositionAndLook = packet.decode()?;
 
-    if bytes_read <= buf_len {
-        // length
-        // id
-        // payload
-
-        varint_read(&mut buf).unwrap();
-
-        varint_read(&mut buf).unwrap();
+    let location = match &mut session.location {
+        Some(location) => {
+            location.teleport(&teleport);
+            location
+        }
+        None => session.location.insert(Location::from_teleport(&teleport)),
+    };
 
-        let payload_from_ping_request: u64 = 92233720u64;
-        let payload_received: u64 =
-            u64::from_be_bytes(buf.drain(..8).collect::<Vec<u8>>().try_into().unwrap());
+    connection.write_packet(&TeleportConfirm {
+        teleport_id: teleport.teleport_id,
+    })?;
+    connection.write_packet(&location.position_and_rotation())
+}
 
-        if payload_from_ping_request == payload_received {
-            Ok(())
-        } else {
-            Err(io::Error::new(io::ErrorKind::Other, "Error at ping - pong"))
-        }
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Error reading from stream",
-        ))
+fn print_location(session: Option<&Session>) {
+    match session.and_then(|session| session.location) {
+        Some(location) => println!(
+            "Position: {:.2} {:.2} {:.2}, yaw {:.1}, pitch {:.1}",
+            location.x, location.y, location.z, location.yaw, location.pitch
+        ),
+        None => println!("The server has not placed the player yet"),
     }
 }
 
-fn keep_alive(stream: &mut TcpStream, mut payload: Vec<u8>) -> io::Result<()> {
-    let mut keep_alive_packet: Vec<u8> = vec![];
-
-    keep_alive_packet.append(&mut varint_write(0x0Fi32)); //id 0x21
-
-    keep_alive_packet.append(&mut payload);
-
-    let mut packet_length: Vec<u8> = varint_write(keep_alive_packet.len() as i32);
-
-    packet_length.append(&mut keep_alive_packet);
-
-    stream.write_all(&packet_length).unwrap();
-
-    Ok(())
+fn print_session_info(session: Option<&Session>) {
+    let Some(session) = session else {
+        println!("Not in the game yet");
+        return;
+    };
+    let name = |game_mode: Option<GameMode>| game_mode.map_or("unknown", GameMode::name);
+
+    println!("Entity id: {}", session.entity_id);
+    println!(
+        "Game mode: {}{}",
+        name(session.game_mode),
+        if session.hardcore { " (hardcore)" } else { "" }
+    );
+    println!("Dimension: {}", session.dimension);
+    match session.difficulty {
+        Some(difficulty) => println!(
+            "Difficulty: {}{}",
+            difficulty.name(),
+            if session.difficulty_locked {
+                " (locked)"
+            } else {
+                ""
+            }
+        ),
+        None => println!("Difficulty: unknown"),
+    }
+    println!(
+        "View distance: {}, simulation distance: {}",
+        session.view_distance, session.simulation_distance
+    );
+    println!(
+        "Weather: {}",
+        if session.raining { "rain" } else { "clear" }
+    );
+    println!(
+        "Health: {:.1}, food: {}, saturation: {:.1}",
+        session.health, session.food, session.saturation
+    );
 }
 
-fn pong(stream: &mut TcpStream, mut payload: Vec<u8>) -> io::Result<()> {
-    println!("Sending Pong...");
-
-    let mut pong_packet: Vec<u8> = vec![];
+// the packets that only exist after Join Game
+fn joined(session: &mut Option<Session>) -> Result<&mut Session> {
+    session
+        .as_mut()
+        .ok_or_else(|| Error::protocol("Play packet received before Join Game"))
+}
 
-    pong_packet.append(&mut varint_write(0x30i32)); //id 0x21
+fn respawn(connection: &mut Connection) -> Result<()> {
+    println!("Respawning...");
 
-    pong_packet.append(&mut payload);
+    connection.write_packet(&ClientStatus {
+        action: PERFORM_RESPAWN,
+    })
+}
 
-    let mut packet_length: Vec<u8> = varint_write(pong_packet.len() as i32);
+// prints the death message of the player, which names the killer, and respawns if asked to or
+// if the server has the respawn screen disabled
+fn combat_event(
+    connection: &mut Connection,
+    packet: &RawPacket,
+    session: &Session,
+    auto_respawn: bool,
+    translations: &Translations,
+) -> Result<()> {
+    let CombatEvent::EntityDead {
+        player_id, message, ..
+    } = packet.decode()?
+    else {
+        return Ok(());
+    };
+    if player_id != session.entity_id {
+        return Ok(());
+    }
 
-    packet_length.append(&mut pong_packet);
+    println!(
+        "\x1B[91mYou died!\x1B[0m {}",
+        ChatComponent::from_json_lossy(&message).to_ansi(translations)
+    );
 
-    stream.write_all(&packet_length).unwrap();
+    if auto_respawn || !session.respawn_screen {
+        respawn(connection)
+    } else {
+        println!("Type respawn to respawn");
+        Ok(())
+    }
+}
 
-    println!("Pong packet sent.");
+// sends the lines the console could not complete itself, one request at a time
+fn request_completions(
+    connection: &mut Connection,
+    console: &Console,
+    pending_completion: &mut Option<ServerboundTabComplete>,
+) -> Result<()> {
+    while let Ok(text) = console.completion_requests.try_recv() {
+        let request = ServerboundTabComplete {
+            transaction_id: pending_completion
+                .as_ref()
+                .map_or(0, |pending| pending.transaction_id + 1),
+            text,
+        };
+        connection.write_packet(&request)?;
+        *pending_completion = Some(request);
+    }
 
     Ok(())
 }
 
-fn update_player_list(
-    buf: &mut Vec<u8>,
-    number_of_players: i32,
-    online_players: &mut HashMap<u128, String>,
-) -> io::Result<()> {
-    // println!("Updating Player List...");
-    // packet format
-    // uuid u128
-    // name_length (varint)
-    // name (string)
-    // number of properties (varint)
-    // array of properties
-    // property_name_length (varint)
-    // property_name (string)
-    // property_value_length (varint)
-    // property_value (string)
-    // property_is_signed (bool) - daca e true, mai avem 2 campuri
-    // property_signature_length (varint)
-    // property_signature (string)
-    // game_mode (varint)
-    // ping (varint)
-    // has_display_name (bool)
-    // display_name (nbt)
-
-    for _player in 0..number_of_players {
-        let uuid: u128 =
-            u128::from_be_bytes(buf.drain(..16).collect::<Vec<u8>>().try_into().unwrap());
-
-        let username_length = varint_read(buf).unwrap() as usize;
-
-        let username =
-            String::from_utf8(buf.drain(..username_length).collect::<Vec<u8>>()).unwrap();
-        online_players.entry(uuid).or_insert(username);
-
-        let number_of_properties = varint_read(buf).unwrap();
-
-        for _ in 0..number_of_properties {
-            let property_name_length = varint_read(buf).unwrap() as usize; // property name
-            String::from_utf8(buf.drain(..property_name_length).collect::<Vec<u8>>()).unwrap();
-
-            let property_value_length = varint_read(buf).unwrap() as usize; // property value
-            String::from_utf8(buf.drain(..property_value_length).collect::<Vec<u8>>()).unwrap();
-
-            let property_is_signed = varint_read(buf).unwrap();
-
-            if property_is_signed == 1 {
-                let property_signature_length = varint_read(buf).unwrap() as usize; // property signature
-                String::from_utf8(buf.drain(..property_signature_length).collect::<Vec<u8>>())
-                    .unwrap();
-            }
-
-            varint_read(buf).unwrap(); // game mode
-
-            varint_read(buf).unwrap(); // ping
-
-            let has_display_name = varint_read(buf).unwrap();
-
-            if has_display_name == 1 {
-                let display_name_length = varint_read(buf); // display name length
-                String::from_utf8(
-                    buf.drain(..display_name_length.unwrap() as usize)
-                        .collect::<Vec<u8>>(),
-                )
-                .unwrap();
-            }
+// completion is optional, a graph that cannot be read (e.g. a modded parser with unknown
+// properties) only means the console asks the server instead
+fn declare_commands(packet: &RawPacket) -> Option<CommandGraph> {
+    match packet.decode() {
+        Ok(commands) => Some(CommandGraph::new(commands)),
+        Err(error) => {
+            eprintln!("Ignoring the declared commands: {}", error);
+            None
         }
     }
-    Ok(())
 }
 
-fn remove_from_online_players(
-    buf: &mut Vec<u8>,
-    number_of_players: i32,
-    online_players: &mut HashMap<u128, String>,
-) -> io::Result<()> {
-    for _player in 0..number_of_players {
-        let uuid: u128 =
-            u128::from_be_bytes(buf.drain(..16).collect::<Vec<u8>>().try_into().unwrap());
-
-        online_players.remove(&uuid);
-    }
+fn tab_complete_response(
+    packet: &RawPacket,
+    console: &Console,
+    pending_completion: &mut Option<ServerboundTabComplete>,
+) -> Result<()> {
+    let response: ClientboundTabComplete = packet.decode()?;
+    let Some(request) =
+        pending_completion.take_if(|pending| pending.transaction_id == response.transaction_id)
+    else {
+        return Ok(());
+    };
+
+    let start = utf16_to_byte_index(&request.text, response.start.max(0) as usize);
+    let matches: Vec<String> = response
+        .matches
+        .into_iter()
+        .map(|suggestion| suggestion.text)
+        .collect();
+    // the console stopped waiting when it is gone, there is no one to tell
+    let _ = console.completions.send(ServerCompletion {
+        text: request.text,
+        start,
+        matches,
+    });
 
     Ok(())
 }
 
-fn player_info(mut buf: Vec<u8>, online_players: &mut HashMap<u128, String>) -> io::Result<()> {
-    let action: i32 = varint_read(&mut buf).unwrap(); // action
+// what the vanilla client sends right after joining, some plugins wait for it
+fn send_brand_and_settings(connection: &mut Connection, args: &Args) -> Result<()> {
+    connection.write_packet(&brand_message(&args.brand))?;
+    connection.write_packet(&args.client_settings())
+}
 
-    let number_of_players = varint_read(&mut buf).unwrap(); // player number
+// prints the brand of the server when it sends it
+struct ServerBrand;
 
-    if action == 0 {
-        update_player_list(&mut buf, number_of_players, online_players).unwrap();
-    } else if action == 4 {
-        remove_from_online_players(&mut buf, number_of_players, online_players).unwrap();
+impl PluginMessageHandler for ServerBrand {
+    fn handle(&mut self, data: &[u8]) -> io::Result<Vec<ServerboundPluginMessage>> {
+        println!("Server brand: {}", read_brand(data)?);
+        Ok(vec![])
     }
-
-    Ok(())
 }
 
-fn send_chat_message(stream: &mut TcpStream, message: &str) -> io::Result<()> {
-    let mut chat_message_packet: Vec<u8> = vec![];
+fn plugin_message(
+    connection: &mut Connection,
+    packet: &RawPacket,
+    plugin_channels: &mut PluginChannels,
+) -> Result<()> {
+    let message: ClientboundPluginMessage = packet.decode()?;
+    println!(
+        "\x1B[2mPlugin message on {} ({} bytes)\x1B[0m",
+        message.channel,
+        message.data.len()
+    );
+
+    // a channel the handler misreads is the plugin's business, the session goes on without it
+    let replies = match plugin_channels.dispatch(&message) {
+        Some(Ok(replies)) => replies,
+        Some(Err(error)) => {
+            eprintln!(
+                "Ignoring the plugin message on {}: {}",
+                message.channel, error
+            );
+            vec![]
+        }
+        None => vec![],
+    };
+    for reply in replies {
+        connection.write_packet(&reply)?;
+    }
 
-    chat_message_packet.append(&mut varint_write(0x03i32)); //id 0x03
+    Ok(())
+}
 
-    chat_message_packet.append(&mut varint_write(message.len() as i32));
+fn send_chat_message(connection: &mut Connection, message: &str) -> Result<()> {
+    let chat_message = ServerboundChatMessage {
+        message: message.to_string(),
+    };
 
-    chat_message_packet.append(&mut message.as_bytes().to_vec());
+    connection.write_packet(&chat_message)
+}
 
-    let mut packet_length: Vec<u8> = varint_write(chat_message_packet.len() as i32); // packet length
+// chat messages in the game info position go to the action bar
+const GAME_INFO: u8 = 2;
 
-    packet_length.append(&mut chat_message_packet);
+fn receive_chat_message(
+    packet: &RawPacket,
+    scoreboard: &Scoreboard,
+    translations: &Translations,
+) -> Result<()> {
+    let chat_message: ClientboundChatMessage = packet.decode()?;
+    let mut component = ChatComponent::from_json_lossy(&chat_message.json);
+    scoreboard.decorate_chat(&mut component);
 
-    stream.write_all(&packet_length).unwrap();
+    if chat_message.position == GAME_INFO {
+        print_title_event(&TitleEvent::ActionBar(component), translations);
+    } else {
+        println!("{}", component.to_ansi(translations));
+    }
 
     Ok(())
 }
 
-fn receive_chat_message(mut buf: Vec<u8>) -> io::Result<()> {
-    let chat_message_length = varint_read(&mut buf).unwrap() as usize;
-
-    let chat_message =
-        String::from_utf8(buf.drain(..chat_message_length).collect::<Vec<u8>>()).unwrap();
-
-    let _position: u8 = buf.remove(0); // position
-
-    let _sender_uuid: u128 =
-        u128::from_be_bytes(buf.drain(..16).collect::<Vec<u8>>().try_into().unwrap()); // sender uuid
-
-    get_chat_message(&chat_message);
+// titles stand out in reverse video, the action bar is dimmed like in the game
+fn print_title_event(event: &TitleEvent, translations: &Translations) {
+    match event {
+        TitleEvent::Title { title, subtitle } => {
+            println!(
+                "\x1B[1;7m TITLE \x1B[0m {}\x1B[0m",
+                title.to_ansi(translations)
+            );
+            if let Some(subtitle) = subtitle {
+                print_title_event(&TitleEvent::Subtitle(subtitle.clone()), translations);
+            }
+        }
+        TitleEvent::Subtitle(subtitle) => {
+            println!(
+                "\x1B[7m       \x1B[0m {}\x1B[0m",
+                subtitle.to_ansi(translations)
+            );
+        }
+        TitleEvent::ActionBar(text) => {
+            println!(
+                "\x1B[2m[action bar]\x1B[0m {}\x1B[0m",
+                text.to_ansi(translations)
+            );
+        }
+    }
+}
 
-    Ok(())
+fn title(action: TitleAction, titles: &mut Titles, translations: &Translations) {
+    if let Some(event) = titles.apply(action) {
+        print_title_event(&event, translations);
+    }
 }
 
-fn main() -> io::Result<()> {
-    let shared_command_queue: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// logs in and handles commands and packets until the user quits (Ok) or the connection ends
+fn play(
+    args: &Args,
+    version: &'static ProtocolVersion,
+    translations: &Translations,
+    response_status_for_printing: Option<&str>,
+    console: &Console,
+) -> Result<()> {
+    let mut connection = connect_to_server(args, version)?;
+    handshake(
+        &mut connection,
+        args,
+        version.protocol,
+        ConnectionState::Login,
+    )?;
+    login_start(&mut connection, &args.username)?;
+    // no login channels are understood yet, proxies fall back to their legacy forwarding
+    let mut login_plugin_channels = LoginPluginChannels::new();
+    login_succes(
+        &mut connection,
+        args.authenticator().as_ref(),
+        &mut login_plugin_channels,
+    )?;
+
+    let mut players = PlayerList::new();
+    let mut titles = Titles::new();
+    let mut boss_bars = BossBars::new();
+    let mut scoreboard = Scoreboard::new();
+    // what the console completes belongs to the server this connection is on
+    *console.source.lock().unwrap() = CompletionSource::default();
+    let mut pending_completion: Option<ServerboundTabComplete> = None;
+    let mut plugin_channels = PluginChannels::new();
+    plugin_channels.register(BRAND, Box::new(ServerBrand));
+    let mut session: Option<Session> = None;
+    let mut last_position_update = Instant::now();
 
-    let command_queue_clone: Arc<Mutex<Vec<String>>> = shared_command_queue.clone();
+    loop {
+        request_completions(&mut connection, console, &mut pending_completion)?;
+        let commands: Vec<String> = console.commands.lock().unwrap().drain(..).collect();
+        for command in commands {
+            match command.as_str() {
+                command if command.split_whitespace().next() == Some("list") => {
+                    let options: Vec<&str> = command.split_whitespace().skip(1).collect();
+                    list_command(&options, &players, &scoreboard, translations);
+                }
+                "tab" => {
+                    print_tab_list(&players, &scoreboard, translations);
+                }
+                "scoreboard" => {
+                    print_sidebar(&scoreboard, &args.username, translations);
+                }
+                "bossbars" => {
+                    print_boss_bars(&boss_bars, translations);
+                }
+                "info" => {
+                    print_session_info(session.as_ref());
+                }
+                "pos" => {
+                    print_location(session.as_ref());
+                }
+                "respawn" => match &session {
+                    Some(session) if session.is_dead() => respawn(&mut connection)?,
+                    _ => println!("The player is not dead"),
+                },
+                "help" => {
+                    help_command();
+                }
+                "status" => {
+                    // a bad status only matters to this command, one that could not be
+                    // fetched before the login is asked for again
+                    let status = match response_status_for_printing {
+                        Some(status) => Ok(status.to_string()),
+                        None => server_status(args),
+                    };
+                    if let Err(error) =
+                        status.and_then(|status| print_status_and_save_favicon(&status))
+                    {
+                        eprintln!("{}", error.describe(translations));
+                    }
+                }
+                "quit" => {
+                    println!("Ok, quitting");
+                    return Ok(());
+                }
+                _ => {
+                    send_chat_message(&mut connection, &command)?;
+                }
+            }
+        }
 
-    thread::spawn(move || {
-        console_reader(command_queue_clone);
-    });
+        // checked on every pass, the packet read below waits at most POLL_INTERVAL
+        if let Some(location) = session.as_ref().and_then(|session| session.location) {
+            if last_position_update.elapsed() >= POSITION_UPDATE_INTERVAL {
+                connection.write_packet(&location.position())?;
+                last_position_update = Instant::now();
+            }
+        }
 
-    let mut response_status_for_printing: Vec<u8> = vec![];
+        // reading packets
 
-    {
-        if let Ok(mut status_stream) = connect_to_server() {
-            // Perform a handshake
+        let Some(packet) = connection.poll_packet()? else {
+            continue;
+        };
 
-            handshake(&mut status_stream, 1).unwrap();
-            response_status_for_printing = request_status(&mut status_stream).unwrap(); // saving for future status request commands
-            ping_request(&mut status_stream).unwrap();
-            ping_response(&mut status_stream).unwrap();
-        } else {
-            println!("Failed to connect to the server.");
+        match packet.kind {
+            Some(PacketKind::ClientboundKeepAlive) => {
+                keep_alive(&mut connection, &packet)?;
+            }
+            Some(PacketKind::PlayPing) => {
+                pong(&mut connection, &packet)?;
+            }
+            Some(PacketKind::PlayerInfo) => {
+                player_info(&packet, &mut players)?;
+                console.source.lock().unwrap().players =
+                    players.iter().map(|player| player.name.clone()).collect();
+            }
+            Some(PacketKind::PlayerListHeaderAndFooter) => {
+                players.set_header_and_footer(&packet.decode()?);
+            }
+            Some(PacketKind::ClientboundChatMessage) => {
+                receive_chat_message(&packet, &scoreboard, translations)?;
+            }
+            Some(PacketKind::BossBar) => {
+                boss_bars.apply(packet.decode()?);
+            }
+            Some(PacketKind::DeclareCommands) => {
+                console.source.lock().unwrap().commands = declare_commands(&packet);
+            }
+            Some(PacketKind::ClientboundTabComplete) => {
+                tab_complete_response(&packet, console, &mut pending_completion)?;
+            }
+            Some(PacketKind::DisplayScoreboard) => {
+                scoreboard.display(packet.decode()?);
+            }
+            Some(PacketKind::ScoreboardObjective) => {
+                scoreboard.objective(packet.decode()?);
+            }
+            Some(PacketKind::UpdateScore) => {
+                scoreboard.score(packet.decode()?);
+            }
+            Some(PacketKind::Teams) => {
+                scoreboard.team(packet.decode()?);
+            }
+            Some(PacketKind::Title) => {
+                title(packet.decode::<Title>()?.action, &mut titles, translations);
+            }
+            Some(PacketKind::SetTitleText) => {
+                title(
+                    packet.decode::<SetTitleText>()?.into(),
+                    &mut titles,
+                    translations,
+                );
+            }
+            Some(PacketKind::SetTitleSubtitle) => {
+                title(
+                    packet.decode::<SetTitleSubtitle>()?.into(),
+                    &mut titles,
+                    translations,
+                );
+            }
+            Some(PacketKind::SetTitleTimes) => {
+                title(
+                    packet.decode::<SetTitleTimes>()?.into(),
+                    &mut titles,
+                    translations,
+                );
+            }
+            Some(PacketKind::ClearTitles) => {
+                title(
+                    packet.decode::<ClearTitles>()?.into(),
+                    &mut titles,
+                    translations,
+                );
+            }
+            Some(PacketKind::ActionBar) => {
+                title(
+                    packet.decode::<ActionBar>()?.into(),
+                    &mut titles,
+                    translations,
+                );
+            }
+            Some(PacketKind::JoinGame) => {
+                session = Some(Session::join(&packet.decode()?));
+                send_brand_and_settings(&mut connection, args)?;
+            }
+            Some(PacketKind::ClientboundPluginMessage) => {
+                plugin_message(&mut connection, &packet, &mut plugin_channels)?;
+            }
+            Some(PacketKind::Respawn) => {
+                joined(&mut session)?.respawn(&packet.decode()?);
+            }
+            Some(PacketKind::ChangeGameState) => {
+                joined(&mut session)?.change_game_state(&packet.decode()?);
+            }
+            Some(PacketKind::ServerDifficulty) => {
+                joined(&mut session)?.set_difficulty(&packet.decode()?);
+            }
+            Some(PacketKind::UpdateHealth) => {
+                joined(&mut session)?.update_health(&packet.decode()?);
+            }
+            Some(PacketKind::CombatEvent) => {
+                combat_event(
+                    &mut connection,
+                    &packet,
+                    joined(&mut session)?,
+                    args.auto_respawn,
+                    translations,
+                )?;
+            }
+            Some(PacketKind::PlayerPositionAndLook) => {
+                teleport(&mut connection, &packet, joined(&mut session)?)?;
+                last_position_update = Instant::now();
+            }
+            Some(PacketKind::Disconnect) => {
+                let disconnect: Disconnect = packet.decode()?;
+                return Err(Error::Disconnected {
+                    reason: Box::new(ChatComponent::from_json_lossy(&disconnect.reason)),
+                });
+            }
+            _ => {}
         }
     }
+}
 
-    if let Ok(mut stream) = connect_to_server() {
-        handshake(&mut stream, 2).unwrap();
-        login_start(&mut stream).unwrap();
-        login_succes(&mut stream).unwrap();
+fn run(args: &Args, translations: &Translations) -> Result<()> {
+    // saving for future status request commands, it also tells which version the server runs
+    let (protocol, response_status_for_printing) = match args.protocol_version {
+        // the login does not need the status when the version is given
+        Some(protocol) if args.mode == Mode::Chat => {
+            let status = server_status(args)
+                .map_err(|error| {
+                    eprintln!(
+                        "Could not get the server status: {}",
+                        error.describe(translations)
+                    )
+                })
+                .ok();
+            (protocol, status)
+        }
+        Some(protocol) => (protocol, Some(server_status(args)?)),
+        None => {
+            let status = server_status(args)?;
+            (detect_protocol(&status)?, Some(status))
+        }
+    };
+    let version = protocol_version(protocol)?;
 
-        let mut online_players: HashMap<u128, String> = HashMap::new();
+    // status mode always has the status, it is only optional in chat mode
+    if let (Mode::Status, Some(status)) = (args.mode, &response_status_for_printing) {
+        println!("Server version: {} ({})", version.name, version.protocol);
+        return print_status_and_save_favicon(status);
+    }
 
-        loop {
-            // reading packets
+    let console = console::spawn();
 
-            let mut command_queue = shared_command_queue.lock().unwrap();
-            for command in command_queue.iter() {
-                match command.as_str() {
-                    "list" => {
-                        println!("Online Players: {:?}", online_players);
-                    }
-                    "help" => {
-                        help_command();
-                    }
-                    "status" => {
-                        print_status_and_save_favicon(&mut response_status_for_printing).unwrap();
-                    }
-                    "quit" => {
-                        println!("Ok, quitting");
-                        return Ok(());
-                    }
-                    _ => {
-                        send_chat_message(&mut stream, command).unwrap();
-                    }
-                }
+    let mut reconnects = 0;
+    loop {
+        match play(
+            args,
+            version,
+            translations,
+            response_status_for_printing.as_deref(),
+            &console,
+        ) {
+            Err(error) if error.is_transient() && reconnects < args.reconnect => {
+                reconnects += 1;
+                eprintln!(
+                    "{}, reconnecting in {} seconds ({}/{})",
+                    error.describe(translations),
+                    RECONNECT_DELAY.as_secs(),
+                    reconnects,
+                    args.reconnect
+                );
+                thread::sleep(RECONNECT_DELAY);
             }
-            command_queue.clear();
+            result => return result,
+        }
+    }
+}
 
-            let mut packet_varint: Vec<u8> = vec![];
+fn main() -> ExitCode {
+    let args = Args::parse();
 
-            loop {
-                let mut init = vec![0u8];
-                stream.read_exact(&mut init).unwrap();
-                let current_byte = init.remove(0);
-                if (current_byte as i32 & CONTINUE_BIT) == 0 {
-                    packet_varint.push(current_byte);
-                    break;
-                } else {
-                    packet_varint.push(current_byte);
+    let translations = match args.translations() {
+        Ok(translations) => translations,
+        Err(error) => {
+            eprintln!("Could not load the language file: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&args, &translations) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            match &error {
+                Error::Disconnected { reason } => {
+                    eprintln!("Kicked: {}", reason.to_ansi(&translations))
                 }
+                _ => eprintln!("{}", error),
             }
-
-            let packet_length = varint_read(&mut packet_varint).unwrap() as usize;
-
-            let mut buf_packet: Vec<u8> = vec![0u8; packet_length];
-            stream.read_exact(&mut buf_packet).unwrap(); // the packet is in buf_packet starting with id
-
-            let id = varint_read(&mut buf_packet).unwrap();
-
-            if id == varint_read(vec![0x21].as_mut()).unwrap() {
-                keep_alive(&mut stream, buf_packet).unwrap();
-            } else if id == varint_read(vec![0x30].as_mut()).unwrap() {
-                pong(&mut stream, buf_packet).unwrap();
-            } else if id == varint_read(vec![0x36].as_mut()).unwrap() {
-                // player info
-                player_info(buf_packet, &mut online_players).unwrap();
-            } else if id == varint_read(vec![0x3C].as_mut()).unwrap() {
-                // player info update
-                println!("received player info update"); // nu l trimite aparent
-            } else if id == varint_read(vec![0x0F].as_mut()).unwrap() {
-                // chat message clientbound
-                receive_chat_message(buf_packet).unwrap();
-            } else if id == varint_read(vec![0x1A].as_mut()).unwrap() {
-                // client disconnected
-                println!("Player disconnected");
-                return Ok(());
-            }
+            ExitCode::from(error.exit_code())
         }
-    } else {
-        println!("Failed to connect to the server.");
     }
-
-    Ok(())
 }
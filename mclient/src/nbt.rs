@@ -0,0 +1,1367 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, SerializeMap};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// the vanilla limit on how deep compounds and lists may nest
+const MAX_DEPTH: usize = 512;
+
+pub type Compound = BTreeMap<String, Tag>;
+
+// A Named Binary Tag value, see https://wiki.vg/NBT
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>), // all elements have the same type
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+const TAG_END: u8 = 0;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl Tag {
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(compound) => compound.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    // any integer tag, widened
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(value) => Some(value as i64),
+            Tag::Short(value) => Some(value as i64),
+            Tag::Int(value) => Some(value as i64),
+            Tag::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_length<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let length = i32::from_be_bytes(read_array(reader)?);
+    if length < 0 {
+        return Err(invalid(format!("Negative NBT length {}", length)));
+    }
+    Ok(length as usize)
+}
+
+// reads elements one by one, so a bogus length fails at the end of the data instead of
+// allocating it all up front
+fn read_elements<R: Read, T>(
+    reader: &mut R,
+    mut read_element: impl FnMut(&mut R) -> io::Result<T>,
+) -> io::Result<Vec<T>> {
+    let length = read_length(reader)?;
+    let mut elements = Vec::with_capacity(length.min(1024));
+    for _ in 0..length {
+        elements.push(read_element(reader)?);
+    }
+    Ok(elements)
+}
+
+// Java's modified UTF-8: the UTF-16 code units of the string, each encoded like UTF-8 on
+// its own (so characters outside the BMP take two 3 byte surrogates) and \0 as C0 80
+fn decode_mutf8(bytes: &[u8]) -> io::Result<String> {
+    let malformed = || invalid("Malformed modified UTF-8 in NBT string".to_string());
+    let continuation = |index: usize| match bytes.get(index) {
+        Some(byte) if byte & 0xC0 == 0x80 => Ok((byte & 0x3F) as u16),
+        _ => Err(malformed()),
+    };
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i] as u16;
+        let (unit, length) = match bytes[i] {
+            0x01..=0x7F => (byte, 1),
+            0xC0..=0xDF => ((byte & 0x1F) << 6 | continuation(i + 1)?, 2),
+            0xE0..=0xEF => (
+                (byte & 0x0F) << 12 | continuation(i + 1)? << 6 | continuation(i + 2)?,
+                3,
+            ),
+            _ => return Err(malformed()),
+        };
+        units.push(unit);
+        i += length;
+    }
+
+    // java strings may hold unpaired surrogates, they are replaced rather than rejected
+    Ok(String::from_utf16_lossy(&units))
+}
+
+fn encode_mutf8(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7F => bytes.push(unit as u8),
+            0x00 | 0x80..=0x7FF => {
+                bytes.push(0xC0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            }
+            _ => {
+                bytes.push(0xE0 | (unit >> 12) as u8);
+                bytes.push(0x80 | (unit >> 6 & 0x3F) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            }
+        }
+    }
+    bytes
+}
+
+fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
+    let length = u16::from_be_bytes(read_array(reader)?);
+    let mut bytes = vec![0u8; length as usize];
+    reader.read_exact(&mut bytes)?;
+    decode_mutf8(&bytes)
+}
+
+fn read_payload<R: Read>(reader: &mut R, id: u8, depth: usize) -> io::Result<Tag> {
+    if depth > MAX_DEPTH {
+        return Err(invalid("NBT nested too deep".to_string()));
+    }
+
+    Ok(match id {
+        1 => Tag::Byte(i8::from_be_bytes(read_array(reader)?)),
+        2 => Tag::Short(i16::from_be_bytes(read_array(reader)?)),
+        3 => Tag::Int(i32::from_be_bytes(read_array(reader)?)),
+        4 => Tag::Long(i64::from_be_bytes(read_array(reader)?)),
+        5 => Tag::Float(f32::from_be_bytes(read_array(reader)?)),
+        6 => Tag::Double(f64::from_be_bytes(read_array(reader)?)),
+        7 => Tag::ByteArray(read_elements(reader, |reader| {
+            Ok(i8::from_be_bytes(read_array(reader)?))
+        })?),
+        8 => Tag::String(read_string(reader)?),
+        9 => {
+            let element_id = read_array::<R, 1>(reader)?[0];
+            let elements =
+                read_elements(reader, |reader| read_payload(reader, element_id, depth + 1))?;
+            if element_id == TAG_END && !elements.is_empty() {
+                return Err(invalid("NBT list of TAG_End".to_string()));
+            }
+            Tag::List(elements)
+        }
+        10 => {
+            let mut compound = Compound::new();
+            loop {
+                let id = read_array::<R, 1>(reader)?[0];
+                if id == TAG_END {
+                    break;
+                }
+                let name = read_string(reader)?;
+                compound.insert(name, read_payload(reader, id, depth + 1)?);
+            }
+            Tag::Compound(compound)
+        }
+        11 => Tag::IntArray(read_elements(reader, |reader| {
+            Ok(i32::from_be_bytes(read_array(reader)?))
+        })?),
+        12 => Tag::LongArray(read_elements(reader, |reader| {
+            Ok(i64::from_be_bytes(read_array(reader)?))
+        })?),
+        _ => return Err(invalid(format!("Unknown NBT tag {}", id))),
+    })
+}
+
+// the root of network NBT since 1.20.2: type and payload, no name. None for a lone TAG_End
+pub fn read_network<R: Read>(reader: &mut R) -> io::Result<Option<Tag>> {
+    let id = read_array::<R, 1>(reader)?[0];
+    if id == TAG_END {
+        return Ok(None);
+    }
+    Ok(Some(read_payload(reader, id, 0)?))
+}
+
+// the root of files and of network NBT before 1.20.2: type, name and payload
+pub fn read_named<R: Read>(reader: &mut R) -> io::Result<Option<(String, Tag)>> {
+    let id = read_array::<R, 1>(reader)?[0];
+    if id == TAG_END {
+        return Ok(None);
+    }
+    let name = read_string(reader)?;
+    Ok(Some((name, read_payload(reader, id, 0)?)))
+}
+
+// string values, compound keys and root names alike
+fn write_string(buf: &mut Vec<u8>, value: &str) -> io::Result<()> {
+    let bytes = encode_mutf8(value);
+    let length = u16::try_from(bytes.len())
+        .map_err(|_| invalid(format!("NBT string of {} bytes", bytes.len())))?;
+    buf.extend_from_slice(&length.to_be_bytes());
+    buf.extend_from_slice(&bytes);
+    Ok(())
+}
+
+fn write_payload(buf: &mut Vec<u8>, tag: &Tag) -> io::Result<()> {
+    match tag {
+        Tag::Byte(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Short(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Int(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Long(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Float(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::Double(value) => buf.extend_from_slice(&value.to_be_bytes()),
+        Tag::ByteArray(values) => {
+            buf.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            buf.extend(values.iter().map(|value| *value as u8));
+        }
+        Tag::String(value) => write_string(buf, value)?,
+        Tag::List(elements) => {
+            let element_id = elements.first().map_or(TAG_END, Tag::id);
+            if elements.iter().any(|element| element.id() != element_id) {
+                return Err(invalid(
+                    "NBT list with elements of different types".to_string(),
+                ));
+            }
+            buf.push(element_id);
+            buf.extend_from_slice(&(elements.len() as i32).to_be_bytes());
+            for element in elements {
+                write_payload(buf, element)?;
+            }
+        }
+        Tag::Compound(compound) => {
+            for (name, value) in compound {
+                buf.push(value.id());
+                write_string(buf, name)?;
+                write_payload(buf, value)?;
+            }
+            buf.push(TAG_END);
+        }
+        Tag::IntArray(values) => {
+            buf.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            for value in values {
+                buf.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        Tag::LongArray(values) => {
+            buf.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            for value in values {
+                buf.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn write_network(tag: &Tag) -> io::Result<Vec<u8>> {
+    let mut buf = vec![tag.id()];
+    write_payload(&mut buf, tag)?;
+    Ok(buf)
+}
+
+pub fn write_named(name: &str, tag: &Tag) -> io::Result<Vec<u8>> {
+    let mut buf = vec![tag.id()];
+    write_string(&mut buf, name)?;
+    write_payload(&mut buf, tag)?;
+    Ok(buf)
+}
+
+// .dat files like level.dat: a named root, usually gzipped
+pub fn read_file(path: &Path) -> io::Result<(String, Tag)> {
+    let mut data = vec![];
+    BufReader::new(File::open(path)?).read_to_end(&mut data)?;
+
+    let named = if data.starts_with(&[0x1f, 0x8b]) {
+        read_named(&mut GzDecoder::new(data.as_slice()))?
+    } else {
+        read_named(&mut data.as_slice())?
+    };
+    named.ok_or_else(|| invalid(format!("{} has no NBT root", path.display())))
+}
+
+pub fn write_file(path: &Path, name: &str, tag: &Tag) -> io::Result<()> {
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(path)?), Compression::default());
+    encoder.write_all(&write_named(name, tag)?)?;
+    encoder.finish()?.flush()
+}
+
+// SNBT, the text format of commands: {name:"Steve",pos:[1.0d,64.0d,2.5d],flags:[B;1b,0b]}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.+".contains(c))
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn write_snbt(out: &mut String, tag: &Tag, indent: Option<usize>) {
+    fn numbers<T: fmt::Display>(out: &mut String, prefix: &str, values: &[T], suffix: &str) {
+        out.push('[');
+        out.push_str(prefix);
+        let values: Vec<String> = values
+            .iter()
+            .map(|value| format!("{}{}", value, suffix))
+            .collect();
+        out.push_str(&values.join(","));
+        out.push(']');
+    }
+
+    // compounds and lists get one entry per line when pretty printing
+    fn entries<T>(
+        out: &mut String,
+        (open, close): (char, char),
+        items: impl ExactSizeIterator<Item = T>,
+        indent: Option<usize>,
+        mut write_item: impl FnMut(&mut String, T, Option<usize>),
+    ) {
+        out.push(open);
+        let empty = items.len() == 0;
+        for (i, item) in items.enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            if let Some(level) = indent {
+                out.push('\n');
+                out.push_str(&"    ".repeat(level + 1));
+            }
+            write_item(out, item, indent.map(|level| level + 1));
+        }
+        if let (Some(level), false) = (indent, empty) {
+            out.push('\n');
+            out.push_str(&"    ".repeat(level));
+        }
+        out.push(close);
+    }
+
+    match tag {
+        Tag::Byte(value) => out.push_str(&format!("{}b", value)),
+        Tag::Short(value) => out.push_str(&format!("{}s", value)),
+        Tag::Int(value) => out.push_str(&value.to_string()),
+        Tag::Long(value) => out.push_str(&format!("{}L", value)),
+        Tag::Float(value) => out.push_str(&format!("{:?}f", value)),
+        Tag::Double(value) => out.push_str(&format!("{:?}d", value)),
+        Tag::String(value) => out.push_str(&quote(value)),
+        Tag::ByteArray(values) => numbers(out, "B;", values, "b"),
+        Tag::IntArray(values) => numbers(out, "I;", values, ""),
+        Tag::LongArray(values) => numbers(out, "L;", values, "L"),
+        Tag::List(elements) => entries(out, ('[', ']'), elements.iter(), indent, write_snbt),
+        Tag::Compound(compound) => entries(
+            out,
+            ('{', '}'),
+            compound.iter(),
+            indent,
+            |out, (key, value), indent| {
+                if is_bare_key(key) {
+                    out.push_str(key);
+                } else {
+                    out.push_str(&quote(key));
+                }
+                out.push_str(if indent.is_some() { ": " } else { ":" });
+                write_snbt(out, value, indent);
+            },
+        ),
+    }
+}
+
+impl Tag {
+    pub fn to_snbt(&self) -> String {
+        let mut out = String::new();
+        write_snbt(&mut out, self, None);
+        out
+    }
+
+    // indented with four spaces, one entry per line
+    pub fn to_snbt_pretty(&self) -> String {
+        let mut out = String::new();
+        write_snbt(&mut out, self, Some(0));
+        out
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_snbt())
+    }
+}
+
+// Serde: to_tag serializes any Serialize type straight into tags, keeping the width of its
+// numbers (bool and u8 become Byte, i16 Short, f32 Float, ...). Unsigned numbers that do not
+// fit the signed tag of their width are an error rather than widened. Sequences of only
+// bytes, ints or longs become the array tags, other sequences lists, and None fields are
+// left out of compounds. from_tag reads tags back into any Deserialize type
+
+// the newtype names Tag serializes its arrays and lists under, so to_tag keeps their type
+// where the element types alone would pick another one. Other serializers ignore them
+const BYTE_ARRAY: &str = "$nbt::ByteArray";
+const INT_ARRAY: &str = "$nbt::IntArray";
+const LONG_ARRAY: &str = "$nbt::LongArray";
+const LIST: &str = "$nbt::List";
+
+#[derive(Debug)]
+struct SerdeError(String);
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        SerdeError(message.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        SerdeError(message.to_string())
+    }
+}
+
+fn out_of_range(value: impl fmt::Display, tag: &str) -> SerdeError {
+    SerdeError(format!("{} does not fit in an NBT {}", value, tag))
+}
+
+fn list(elements: Vec<Tag>) -> Result<Tag, SerdeError> {
+    let element_id = elements.first().map(Tag::id);
+    if elements
+        .iter()
+        .any(|element| Some(element.id()) != element_id)
+    {
+        return Err(SerdeError(
+            "NBT list with elements of different types".to_string(),
+        ));
+    }
+    Ok(Tag::List(elements))
+}
+
+// the elements of an array tag, all of the tag type `extract` accepts
+fn array<T>(
+    elements: Vec<Tag>,
+    name: &str,
+    extract: impl Fn(Tag) -> Option<T>,
+) -> Result<Vec<T>, SerdeError> {
+    elements
+        .into_iter()
+        .map(|element| extract(element).ok_or_else(|| SerdeError(format!("{} element", name))))
+        .collect()
+}
+
+// Ok(None) is a value NBT has no tag for (None, unit), compounds leave those out
+#[derive(Default)]
+struct TagSerializer {
+    sequence: Option<&'static str>, // one of the newtype names, for the next sequence
+}
+
+struct SeqSerializer {
+    elements: Vec<Tag>,
+    sequence: Option<&'static str>,
+}
+
+#[derive(Default)]
+struct CompoundSerializer {
+    compound: Compound,
+    key: Option<String>,
+}
+
+// enum variants with data become a compound with the variant name as the only key
+struct VariantSerializer<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+fn variant(variant: &str, tag: Option<Tag>) -> Option<Tag> {
+    let mut compound = Compound::new();
+    if let Some(tag) = tag {
+        compound.insert(variant.to_string(), tag);
+    }
+    Some(Tag::Compound(compound))
+}
+
+impl ser::Serializer for TagSerializer {
+    type Ok = Option<Tag>;
+    type Error = SerdeError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = CompoundSerializer;
+    type SerializeStruct = CompoundSerializer;
+    type SerializeStructVariant = VariantSerializer<CompoundSerializer>;
+
+    fn serialize_bool(self, value: bool) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Byte(value as i8)))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Byte(value)))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Short(value)))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Int(value)))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Long(value)))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Option<Tag>, SerdeError> {
+        let value = i8::try_from(value).map_err(|_| out_of_range(value, "Byte"))?;
+        Ok(Some(Tag::Byte(value)))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Option<Tag>, SerdeError> {
+        let value = i16::try_from(value).map_err(|_| out_of_range(value, "Short"))?;
+        Ok(Some(Tag::Short(value)))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Option<Tag>, SerdeError> {
+        let value = i32::try_from(value).map_err(|_| out_of_range(value, "Int"))?;
+        Ok(Some(Tag::Int(value)))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Option<Tag>, SerdeError> {
+        let value = i64::try_from(value).map_err(|_| out_of_range(value, "Long"))?;
+        Ok(Some(Tag::Long(value)))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Float(value)))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Double(value)))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::String(value.to_string())))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::String(value.to_string())))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::ByteArray(
+            value.iter().map(|byte| *byte as i8).collect(),
+        )))
+    }
+
+    fn serialize_none(self) -> Result<Option<Tag>, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Option<Tag>, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<Tag>, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<Tag>, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::String(variant.to_string())))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Option<Tag>, SerdeError> {
+        match name {
+            BYTE_ARRAY | INT_ARRAY | LONG_ARRAY | LIST => value.serialize(TagSerializer {
+                sequence: Some(name),
+            }),
+            _ => value.serialize(self),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Option<Tag>, SerdeError> {
+        Ok(variant(name, value.serialize(TagSerializer::default())?))
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<SeqSerializer, SerdeError> {
+        Ok(SeqSerializer {
+            elements: Vec::with_capacity(length.unwrap_or(0).min(1024)),
+            sequence: self.sequence,
+        })
+    }
+
+    fn serialize_tuple(self, length: usize) -> Result<SeqSerializer, SerdeError> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        length: usize,
+    ) -> Result<SeqSerializer, SerdeError> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        length: usize,
+    ) -> Result<VariantSerializer<SeqSerializer>, SerdeError> {
+        Ok(VariantSerializer {
+            variant,
+            inner: TagSerializer::default().serialize_seq(Some(length))?,
+        })
+    }
+
+    fn serialize_map(self, _length: Option<usize>) -> Result<CompoundSerializer, SerdeError> {
+        Ok(CompoundSerializer::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _length: usize,
+    ) -> Result<CompoundSerializer, SerdeError> {
+        Ok(CompoundSerializer::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _length: usize,
+    ) -> Result<VariantSerializer<CompoundSerializer>, SerdeError> {
+        Ok(VariantSerializer {
+            variant,
+            inner: CompoundSerializer::default(),
+        })
+    }
+}
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let element = value
+            .serialize(TagSerializer::default())?
+            .ok_or_else(|| SerdeError("None has no NBT equivalent in a list".to_string()))?;
+        self.elements.push(element);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Tag>, SerdeError> {
+        let elements = self.elements;
+        let all = |id: u8| !elements.is_empty() && elements.iter().all(|e| e.id() == id);
+        let sequence = match self.sequence {
+            Some(sequence) => sequence,
+            None if all(1) => BYTE_ARRAY,
+            None if all(3) => INT_ARRAY,
+            None if all(4) => LONG_ARRAY,
+            None => LIST,
+        };
+
+        Ok(Some(match sequence {
+            BYTE_ARRAY => Tag::ByteArray(array(elements, "Byte array", |element| match element {
+                Tag::Byte(value) => Some(value),
+                _ => None,
+            })?),
+            INT_ARRAY => Tag::IntArray(array(elements, "Int array", |element| match element {
+                Tag::Int(value) => Some(value),
+                _ => None,
+            })?),
+            LONG_ARRAY => Tag::LongArray(array(elements, "Long array", |element| match element {
+                Tag::Long(value) => Some(value),
+                _ => None,
+            })?),
+            _ => list(elements)?,
+        }))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Option<Tag>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Option<Tag>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Option<Tag>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = Option<Tag>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, SerdeError> {
+        Ok(variant(self.variant, self.inner.finish()?))
+    }
+}
+
+impl CompoundSerializer {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), SerdeError> {
+        if let Some(tag) = value.serialize(TagSerializer::default())? {
+            self.compound.insert(key, tag);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for CompoundSerializer {
+    type Ok = Option<Tag>;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        match key.serialize(TagSerializer::default())? {
+            Some(Tag::String(key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(SerdeError("NBT compound keys must be strings".to_string())),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerdeError("Map value without a key".to_string()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Compound(self.compound)))
+    }
+}
+
+impl ser::SerializeStruct for CompoundSerializer {
+    type Ok = Option<Tag>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, SerdeError> {
+        Ok(Some(Tag::Compound(self.compound)))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<CompoundSerializer> {
+    type Ok = Option<Tag>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.inner.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, SerdeError> {
+        Ok(variant(
+            self.variant,
+            Some(Tag::Compound(self.inner.compound)),
+        ))
+    }
+}
+
+// reads a tag as the type asked for: integer and float tags convert to any Rust number
+// that holds the value, Byte 0 and 1 to bool, String or a single entry compound to an enum
+struct TagDeserializer<'a>(&'a Tag);
+
+impl<'de> IntoDeserializer<'de, SerdeError> for TagDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for TagDeserializer<'_> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Tag::Byte(value) => visitor.visit_i8(*value),
+            Tag::Short(value) => visitor.visit_i16(*value),
+            Tag::Int(value) => visitor.visit_i32(*value),
+            Tag::Long(value) => visitor.visit_i64(*value),
+            Tag::Float(value) => visitor.visit_f32(*value),
+            Tag::Double(value) => visitor.visit_f64(*value),
+            Tag::String(value) => visitor.visit_str(value),
+            Tag::ByteArray(values) => {
+                SeqDeserializer::new(values.iter().copied()).deserialize_any(visitor)
+            }
+            Tag::IntArray(values) => {
+                SeqDeserializer::new(values.iter().copied()).deserialize_any(visitor)
+            }
+            Tag::LongArray(values) => {
+                SeqDeserializer::new(values.iter().copied()).deserialize_any(visitor)
+            }
+            Tag::List(elements) => {
+                SeqDeserializer::new(elements.iter().map(TagDeserializer)).deserialize_any(visitor)
+            }
+            Tag::Compound(compound) => MapDeserializer::new(
+                compound
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), TagDeserializer(value))),
+            )
+            .deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Tag::Byte(0) => visitor.visit_bool(false),
+            Tag::Byte(1) => visitor.visit_bool(true),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    // a tag that is there is always Some, missing fields are None
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Tag::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            Tag::Compound(compound) if compound.len() == 1 => {
+                visitor.visit_enum(MapAccessDeserializer::new(MapDeserializer::new(
+                    compound
+                        .iter()
+                        .map(|(key, value)| (key.as_str(), TagDeserializer(value))),
+                )))
+            }
+            _ => Err(SerdeError(format!(
+                "Expected an enum variant, found {}",
+                self.0
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Tag::Byte(value) => serializer.serialize_i8(*value),
+            Tag::Short(value) => serializer.serialize_i16(*value),
+            Tag::Int(value) => serializer.serialize_i32(*value),
+            Tag::Long(value) => serializer.serialize_i64(*value),
+            Tag::Float(value) => serializer.serialize_f32(*value),
+            Tag::Double(value) => serializer.serialize_f64(*value),
+            Tag::String(value) => serializer.serialize_str(value),
+            Tag::ByteArray(values) => serializer.serialize_newtype_struct(BYTE_ARRAY, values),
+            Tag::IntArray(values) => serializer.serialize_newtype_struct(INT_ARRAY, values),
+            Tag::LongArray(values) => serializer.serialize_newtype_struct(LONG_ARRAY, values),
+            Tag::List(elements) => serializer.serialize_newtype_struct(LIST, elements),
+            Tag::Compound(compound) => {
+                let mut map = serializer.serialize_map(Some(compound.len()))?;
+                for (key, value) in compound {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+// a tag from any self-describing format like JSON. Without a schema the narrowest types
+// are guessed: integers become Int or Long, booleans Byte. Nulls are left out of compounds
+struct MaybeTag(Option<Tag>);
+
+struct TagVisitor;
+
+fn narrowest(value: i64) -> Tag {
+    match i32::try_from(value) {
+        Ok(value) => Tag::Int(value),
+        Err(_) => Tag::Long(value),
+    }
+}
+
+impl<'de> Visitor<'de> for TagVisitor {
+    type Value = MaybeTag;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a value NBT can hold")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(Tag::Byte(value as i8))))
+    }
+
+    fn visit_i8<E: de::Error>(self, value: i8) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(Tag::Byte(value))))
+    }
+
+    fn visit_i16<E: de::Error>(self, value: i16) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(Tag::Short(value))))
+    }
+
+    fn visit_i32<E: de::Error>(self, value: i32) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(Tag::Int(value))))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(narrowest(value))))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<MaybeTag, E> {
+        let value = i64::try_from(value).map_err(|_| E::custom(out_of_range(value, "Long")))?;
+        Ok(MaybeTag(Some(narrowest(value))))
+    }
+
+    fn visit_f32<E: de::Error>(self, value: f32) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(Tag::Float(value))))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(Tag::Double(value))))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(Tag::String(value.to_string()))))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(Some(Tag::ByteArray(
+            value.iter().map(|byte| *byte as i8).collect(),
+        ))))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(None))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<MaybeTag, E> {
+        Ok(MaybeTag(None))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<MaybeTag, D::Error> {
+        MaybeTag::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<MaybeTag, D::Error> {
+        MaybeTag::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<MaybeTag, A::Error> {
+        let mut elements = vec![];
+        while let Some(element) = seq.next_element::<Tag>()? {
+            elements.push(element);
+        }
+
+        // a list of Int and Long because some values were large: widen them all
+        if elements
+            .iter()
+            .any(|element| matches!(element, Tag::Long(_)))
+        {
+            elements = elements
+                .into_iter()
+                .map(|element| match element {
+                    Tag::Int(value) => Tag::Long(value as i64),
+                    element => element,
+                })
+                .collect();
+        }
+        Ok(MaybeTag(Some(list(elements).map_err(de::Error::custom)?)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<MaybeTag, A::Error> {
+        let mut compound = Compound::new();
+        while let Some((key, value)) = map.next_entry::<String, MaybeTag>()? {
+            if let MaybeTag(Some(value)) = value {
+                compound.insert(key, value);
+            }
+        }
+        Ok(MaybeTag(Some(Tag::Compound(compound))))
+    }
+}
+
+impl<'de> Deserialize<'de> for MaybeTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<MaybeTag, D::Error> {
+        deserializer.deserialize_any(TagVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Tag, D::Error> {
+        MaybeTag::deserialize(deserializer)?
+            .0
+            .ok_or_else(|| de::Error::custom("null has no NBT equivalent"))
+    }
+}
+
+pub fn to_tag<T: Serialize + ?Sized>(value: &T) -> io::Result<Tag> {
+    value
+        .serialize(TagSerializer::default())
+        .map_err(|error| invalid(error.0))?
+        .ok_or_else(|| invalid("The value has no NBT equivalent".to_string()))
+}
+
+pub fn from_tag<T: DeserializeOwned>(tag: &Tag) -> io::Result<T> {
+    T::deserialize(TagDeserializer(tag)).map_err(|error| invalid(error.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+
+    fn every_tag() -> Tag {
+        let mut nested = Compound::new();
+        nested.insert("empty list".to_string(), Tag::List(vec![]));
+        nested.insert("empty".to_string(), Tag::Compound(Compound::new()));
+
+        let mut compound = Compound::new();
+        compound.insert("byte".to_string(), Tag::Byte(-1));
+        compound.insert("short".to_string(), Tag::Short(300));
+        compound.insert("int".to_string(), Tag::Int(-70000));
+        compound.insert("long".to_string(), Tag::Long(i64::MIN));
+        compound.insert("float".to_string(), Tag::Float(0.5));
+        compound.insert("double".to_string(), Tag::Double(-1.25));
+        compound.insert("bytes".to_string(), Tag::ByteArray(vec![1, -2, 3]));
+        compound.insert(
+            "name".to_string(),
+            Tag::String("Bananrama \"ö\"".to_string()),
+        );
+        compound.insert(
+            "list".to_string(),
+            Tag::List(vec![Tag::Compound(nested.clone()), Tag::Compound(nested)]),
+        );
+        compound.insert("ints".to_string(), Tag::IntArray(vec![i32::MAX, 0]));
+        compound.insert("longs".to_string(), Tag::LongArray(vec![1, -1]));
+        Tag::Compound(compound)
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let tag = every_tag();
+
+        let network = write_network(&tag).unwrap();
+        assert_eq!(
+            Some(tag.clone()),
+            read_network(&mut network.as_slice()).unwrap()
+        );
+
+        let named = write_named("hello world", &tag).unwrap();
+        assert_eq!(
+            Some(("hello world".to_string(), tag.clone())),
+            read_named(&mut named.as_slice()).unwrap()
+        );
+        // the name is the only difference
+        assert_eq!(network.len() + 2 + 11, named.len());
+
+        assert_eq!(None, read_network(&mut [0u8].as_slice()).unwrap());
+        assert!(read_named(&mut &named[..named.len() - 1]).is_err());
+        assert!(write_network(&Tag::List(vec![Tag::Byte(1), Tag::Int(1)])).is_err());
+    }
+
+    #[test]
+    fn test_known_bytes() {
+        // hello_world.nbt from the NBT specification
+        let mut data = vec![10, 0, 11];
+        data.extend(b"hello world");
+        data.extend([8, 0, 4]);
+        data.extend(b"name");
+        data.extend([0, 9]);
+        data.extend(b"Bananrama");
+        data.push(0);
+
+        let (name, tag) = read_named(&mut data.as_slice()).unwrap().unwrap();
+        assert_eq!("hello world", name);
+        assert_eq!(Some("Bananrama"), tag.get("name").and_then(Tag::as_str));
+        assert_eq!(data, write_named(&name, &tag).unwrap());
+    }
+
+    #[test]
+    fn test_modified_utf8() {
+        let mut compound = Compound::new();
+        compound.insert("display".to_string(), Tag::String("nul\0 é 💎".to_string()));
+        let tag = Tag::Compound(compound);
+        let data = write_network(&tag).unwrap();
+        assert_eq!(Some(tag), read_network(&mut data.as_slice()).unwrap());
+
+        // \0 as C0 80, é as in UTF-8, the emoji as two surrogates of 3 bytes
+        let mut expected = vec![0x00, 0x0F, b'n', b'u', b'l', 0xC0, 0x80, b' ', 0xC3, 0xA9];
+        expected.extend([b' ', 0xED, 0xA0, 0xBD, 0xED, 0xB2, 0x8E]);
+        let mut buf = vec![];
+        write_string(&mut buf, "nul\0 é 💎").unwrap();
+        assert_eq!(expected, buf);
+
+        assert!(read_string(&mut [0x00, 0x01, 0x00].as_slice()).is_err());
+        assert!(read_string(&mut [0x00, 0x02, 0xC3, b'a'].as_slice()).is_err());
+        assert!(read_string(&mut [0x00, 0x04, 0xF0, 0x9F, 0x92, 0x8E].as_slice()).is_err());
+
+        let mut compound = Compound::new();
+        compound.insert("k".repeat(70000), Tag::Byte(1));
+        assert!(write_network(&Tag::Compound(compound)).is_err());
+        assert!(write_named(&"n".repeat(70000), &Tag::Byte(1)).is_err());
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("mclient-{}.dat", std::process::id()));
+        let tag = every_tag();
+
+        write_file(&path, "Data", &tag).unwrap();
+        let gzipped = std::fs::read(&path).unwrap();
+        assert_eq!(&[0x1f, 0x8b], &gzipped[..2]);
+        assert_eq!(("Data".to_string(), tag.clone()), read_file(&path).unwrap());
+
+        // uncompressed files are read as well
+        std::fs::write(&path, write_named("Data", &tag).unwrap()).unwrap();
+        assert_eq!(("Data".to_string(), tag), read_file(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_snbt() {
+        let mut compound = Compound::new();
+        compound.insert("id".to_string(), Tag::String("minecraft:stone".to_string()));
+        compound.insert("Count".to_string(), Tag::Byte(64));
+        compound.insert(
+            "pos".to_string(),
+            Tag::List(vec![Tag::Double(1.0), Tag::Double(-2.5)]),
+        );
+        compound.insert("my key".to_string(), Tag::LongArray(vec![1, 2]));
+        compound.insert("empty".to_string(), Tag::List(vec![]));
+        let tag = Tag::Compound(compound);
+
+        assert_eq!(
+            r#"{Count:64b,empty:[],id:"minecraft:stone","my key":[L;1L,2L],pos:[1.0d,-2.5d]}"#,
+            tag.to_snbt()
+        );
+        assert_eq!(
+            "{\n    Count: 64b,\n    empty: [],\n    id: \"minecraft:stone\",\n    \"my key\": [L;1L,2L],\n    pos: [\n        1.0d,\n        -2.5d\n    ]\n}",
+            tag.to_snbt_pretty()
+        );
+        assert_eq!(
+            "\"say \\\"hi\\\"\"",
+            Tag::String("say \"hi\"".to_string()).to_string()
+        );
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        id: String,
+        count: u8,
+        damage: Option<i32>,
+        enchantments: Vec<Enchantment>,
+        unbreakable: bool,
+        scale: f32,
+        colors: Vec<i8>,
+        slot: Slot,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Slot {
+        Hand,
+        Armor(i16),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Enchantment {
+        id: String,
+        level: i16,
+    }
+
+    #[test]
+    fn test_serde() {
+        let item = Item {
+            id: "minecraft:diamond_sword".to_string(),
+            count: 1,
+            damage: None,
+            enchantments: vec![Enchantment {
+                id: "minecraft:sharpness".to_string(),
+                level: 5,
+            }],
+            unbreakable: true,
+            scale: 0.5,
+            colors: vec![1, -2],
+            slot: Slot::Armor(3),
+        };
+
+        // every field keeps the width of its Rust type, as vanilla expects
+        let tag = to_tag(&item).unwrap();
+        assert_eq!(None, tag.get("damage"));
+        assert_eq!(Some(&Tag::Byte(1)), tag.get("count"));
+        assert_eq!(Some(&Tag::Byte(1)), tag.get("unbreakable"));
+        assert_eq!(Some(&Tag::Float(0.5)), tag.get("scale"));
+        assert_eq!(Some(&Tag::ByteArray(vec![1, -2])), tag.get("colors"));
+        assert_eq!(
+            Some(&Tag::Short(5)),
+            tag.get("enchantments")
+                .and_then(|list| match list {
+                    Tag::List(elements) => elements.first(),
+                    _ => None,
+                })
+                .and_then(|enchantment| enchantment.get("level"))
+        );
+        assert_eq!(
+            Some(&Tag::Short(3)),
+            tag.get("slot").and_then(|slot| slot.get("Armor"))
+        );
+        assert_eq!(item, from_tag::<Item>(&tag).unwrap());
+
+        // numbers that do not fit their tag are an error rather than a Double
+        assert!(to_tag(&200u8).is_err());
+        assert!(to_tag(&u64::MAX).is_err());
+        assert!(to_tag(&vec![Some(1), None]).is_err());
+        assert!(from_tag::<u8>(&Tag::Int(300)).is_err());
+        assert_eq!(
+            Tag::String("Hand".to_string()),
+            to_tag(&Slot::Hand).unwrap()
+        );
+
+        // tags keep their exact type, lists of ints are not turned into arrays
+        let tag = every_tag();
+        assert_eq!(tag, to_tag(&tag).unwrap());
+        let ints = Tag::List(vec![Tag::Int(1), Tag::Int(2)]);
+        assert_eq!(ints, to_tag(&ints).unwrap());
+
+        // the exact tag types of real data deserialize as well
+        let mut compound = Compound::new();
+        compound.insert("id".to_string(), Tag::String("minecraft:stone".to_string()));
+        compound.insert("count".to_string(), Tag::Byte(64));
+        compound.insert("damage".to_string(), Tag::Int(3));
+        compound.insert("enchantments".to_string(), Tag::List(vec![]));
+        compound.insert("unbreakable".to_string(), Tag::Byte(0));
+        compound.insert("scale".to_string(), Tag::Double(1.0));
+        compound.insert("colors".to_string(), Tag::ByteArray(vec![]));
+        compound.insert("slot".to_string(), Tag::String("Hand".to_string()));
+        let stone: Item = from_tag(&Tag::Compound(compound)).unwrap();
+        assert_eq!(64, stone.count);
+        assert_eq!(Some(3), stone.damage);
+
+        let tag: Tag = serde_json::from_str(r#"{"big":[1,5000000000]}"#).unwrap();
+        assert_eq!(
+            Some(&Tag::List(vec![Tag::Long(1), Tag::Long(5000000000)])),
+            tag.get("big")
+        );
+        assert!(serde_json::from_str::<Tag>("18446744073709551615").is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use std::io;
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handshake {
+    pub protocol_version: i32,
+    pub server_address: String,
+    pub server_port: u16,
+    pub next_state: i32, // 1 for status, 2 for login
+}
+
+impl Packet for Handshake {
+    const KIND: PacketKind = PacketKind::Handshake;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.protocol_version));
+        buf.append(&mut string_write(&self.server_address));
+        buf.extend_from_slice(&self.server_port.to_be_bytes());
+        buf.append(&mut varint_write(self.next_state));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(Handshake {
+            protocol_version: reader.read_varint()?,
+            server_address: reader.read_string()?,
+            server_port: reader.read_u16()?,
+            next_state: reader.read_varint()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_bytes() {
+        let handshake = Handshake {
+            protocol_version: 758,
+            server_address: "127.0.0.1".to_string(),
+            server_port: 25565,
+            next_state: 2,
+        };
+
+        let mut expected: Vec<u8> = vec![0xf6, 0x05, 0x09];
+        expected.extend_from_slice(b"127.0.0.1");
+        expected.extend_from_slice(&[0x63, 0xdd, 0x02]);
+
+        assert_eq!(expected, handshake.to_bytes(758).unwrap());
+        assert_eq!(handshake, round_trip(&handshake));
+    }
+}
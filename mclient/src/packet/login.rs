@@ -0,0 +1,253 @@
+use std::io;
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginStart {
+    pub username: String,
+}
+
+impl Packet for LoginStart {
+    const KIND: PacketKind = PacketKind::LoginStart;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.username));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(LoginStart {
+            username: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginDisconnect {
+    pub reason: String, // chat component json
+}
+
+impl Packet for LoginDisconnect {
+    const KIND: PacketKind = PacketKind::LoginDisconnect;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.reason));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(LoginDisconnect {
+            reason: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginSuccess {
+    pub uuid: u128,
+    pub username: String,
+}
+
+impl Packet for LoginSuccess {
+    const KIND: PacketKind = PacketKind::LoginSuccess;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.uuid.to_be_bytes());
+        buf.append(&mut string_write(&self.username));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(LoginSuccess {
+            uuid: reader.read_uuid()?,
+            username: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionRequest {
+    pub server_id: String,   // empty on vanilla servers
+    pub public_key: Vec<u8>, // DER encoded RSA key
+    pub verify_token: Vec<u8>,
+}
+
+impl Packet for EncryptionRequest {
+    const KIND: PacketKind = PacketKind::EncryptionRequest;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.server_id));
+        buf.append(&mut byte_array_write(&self.public_key));
+        buf.append(&mut byte_array_write(&self.verify_token));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(EncryptionRequest {
+            server_id: reader.read_string()?,
+            public_key: reader.read_byte_array()?.to_vec(),
+            verify_token: reader.read_byte_array()?.to_vec(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionResponse {
+    pub shared_secret: Vec<u8>, // both encrypted with the server public key
+    pub verify_token: Vec<u8>,
+}
+
+impl Packet for EncryptionResponse {
+    const KIND: PacketKind = PacketKind::EncryptionResponse;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut byte_array_write(&self.shared_secret));
+        buf.append(&mut byte_array_write(&self.verify_token));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(EncryptionResponse {
+            shared_secret: reader.read_byte_array()?.to_vec(),
+            verify_token: reader.read_byte_array()?.to_vec(),
+        })
+    }
+}
+
+// sent before Login Success when the server has network-compression-threshold enabled,
+// every packet after it uses the compressed frame format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetCompression {
+    pub threshold: i32, // negative disables compression
+}
+
+impl Packet for SetCompression {
+    const KIND: PacketKind = PacketKind::SetCompression;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.threshold));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(SetCompression {
+            threshold: reader.read_varint()?,
+        })
+    }
+}
+
+// sent by the server (or a proxy) to talk to client mods before Login Success
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginPluginRequest {
+    pub message_id: i32,
+    pub channel: String,
+    pub data: Vec<u8>, // up to the end of the packet
+}
+
+impl Packet for LoginPluginRequest {
+    const KIND: PacketKind = PacketKind::LoginPluginRequest;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.message_id));
+        buf.append(&mut string_write(&self.channel));
+        buf.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(LoginPluginRequest {
+            message_id: reader.read_varint()?,
+            channel: reader.read_string()?,
+            data: reader.rest().to_vec(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginPluginResponse {
+    pub message_id: i32,
+    pub data: Option<Vec<u8>>, // None tells the server the channel is not understood
+}
+
+impl Packet for LoginPluginResponse {
+    const KIND: PacketKind = PacketKind::LoginPluginResponse;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.message_id));
+        buf.append(&mut bool_write(self.data.is_some()));
+        if let Some(data) = &self.data {
+            buf.extend_from_slice(data);
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let message_id = reader.read_varint()?;
+        let data = if reader.read_bool()? {
+            Some(reader.rest().to_vec())
+        } else {
+            None
+        };
+
+        Ok(LoginPluginResponse { message_id, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_round_trip() {
+        let start = LoginStart {
+            username: "eudinaltapartee".to_string(),
+        };
+        assert_eq!(start, round_trip(&start));
+
+        let success = LoginSuccess {
+            uuid: 0x069a79f4_44e9_4726_a5be_fca90e38aaf5,
+            username: "Notch".to_string(),
+        };
+        assert_eq!(success, round_trip(&success));
+
+        let disconnect = LoginDisconnect {
+            reason: r#"{"text":"You are banned"}"#.to_string(),
+        };
+        assert_eq!(disconnect, round_trip(&disconnect));
+
+        let request = EncryptionRequest {
+            server_id: "".to_string(),
+            public_key: vec![0x30, 0x81, 0x9f, 0x30, 0x0d],
+            verify_token: vec![1, 2, 3, 4],
+        };
+        assert_eq!(request, round_trip(&request));
+
+        let response = EncryptionResponse {
+            shared_secret: vec![0xaa; 128],
+            verify_token: vec![0xbb; 128],
+        };
+        assert_eq!(response, round_trip(&response));
+
+        let compression = SetCompression { threshold: 256 };
+        assert_eq!(vec![0x80, 0x02], compression.to_bytes(758).unwrap());
+        assert_eq!(compression, round_trip(&compression));
+
+        let plugin_request = LoginPluginRequest {
+            message_id: 7,
+            channel: "velocity:player_info".to_string(),
+            data: vec![1],
+        };
+        assert_eq!(plugin_request, round_trip(&plugin_request));
+        let not_understood = LoginPluginResponse {
+            message_id: 7,
+            data: None,
+        };
+        assert_eq!(vec![0x07, 0x00], not_understood.to_bytes(758).unwrap());
+        assert_eq!(not_understood, round_trip(&not_understood));
+        let understood = LoginPluginResponse {
+            message_id: 8,
+            data: Some(vec![]),
+        };
+        assert_eq!(understood, round_trip(&understood));
+    }
+}
@@ -0,0 +1,96 @@
+use std::io;
+
+use crate::nbt::{self, Tag};
+pub use crate::reader::PacketReader;
+use crate::varint_type::*;
+pub use crate::version::PacketKind;
+use crate::version::{V1_17, V1_18};
+
+mod handshake;
+mod login;
+mod play;
+mod status;
+
+pub use handshake::*;
+pub use login::*;
+pub use play::*;
+pub use status::*;
+
+// Every connection starts out handshaking, the Handshake packet picks Status or Login and
+// Login Success moves a login on to Play. Packet ids only mean something within a state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Handshaking,
+    Status,
+    Login,
+    Play,
+}
+
+impl ConnectionState {
+    pub fn can_transition_to(self, next: ConnectionState) -> bool {
+        use ConnectionState::*;
+        matches!(
+            (self, next),
+            (Handshaking, Status) | (Handshaking, Login) | (Login, Play)
+        )
+    }
+
+    // the next state field of the Handshake packet
+    pub fn handshake_id(self) -> Option<i32> {
+        match self {
+            ConnectionState::Status => Some(1),
+            ConnectionState::Login => Some(2),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Serverbound, // client -> server
+    Clientbound, // server -> client
+}
+
+// A packet knows which logical packet it is, the id comes from the ProtocolVersion table.
+// encode/decode only handle the fields after the id (the protocol number is passed for the
+// packets whose layout changed between versions), the framing (length + id) is done by
+// Connection::write_packet
+pub trait Packet: Sized {
+    const KIND: PacketKind;
+    const STATE: ConnectionState = Self::KIND.state();
+    const DIRECTION: Direction = Self::KIND.direction();
+
+    // fails for fields that cannot be written, like malformed NBT
+    fn encode(&self, buf: &mut Vec<u8>, protocol: i32) -> io::Result<()>;
+
+    fn decode(reader: &mut PacketReader, protocol: i32) -> io::Result<Self>;
+
+    fn to_bytes(&self, protocol: i32) -> io::Result<Vec<u8>> {
+        let mut buf: Vec<u8> = vec![];
+        self.encode(&mut buf, protocol)?;
+        Ok(buf)
+    }
+
+    // decodes the fields of a packet whose id was already read
+    fn from_bytes(data: &[u8], protocol: i32) -> io::Result<Self> {
+        Self::decode(&mut PacketReader::new(data), protocol)
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn round_trip<P: Packet>(packet: &P) -> P {
+    round_trip_version(packet, crate::version::LATEST.protocol)
+}
+
+#[cfg(test)]
+pub(crate) fn round_trip_version<P: Packet>(packet: &P, protocol: i32) -> P {
+    let data = packet.to_bytes(protocol).unwrap();
+    let mut reader = PacketReader::new(&data);
+    let decoded = P::decode(&mut reader, protocol).unwrap();
+    assert!(
+        reader.is_empty(),
+        "decode left {} bytes",
+        reader.remaining()
+    );
+    decoded
+}
@@ -0,0 +1,2339 @@
+use std::io;
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientboundKeepAlive {
+    pub keep_alive_id: i64,
+}
+
+impl Packet for ClientboundKeepAlive {
+    const KIND: PacketKind = PacketKind::ClientboundKeepAlive;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.keep_alive_id.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ClientboundKeepAlive {
+            keep_alive_id: reader.read_i64()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerboundKeepAlive {
+    pub keep_alive_id: i64,
+}
+
+impl Packet for ServerboundKeepAlive {
+    const KIND: PacketKind = PacketKind::ServerboundKeepAlive;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.keep_alive_id.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ServerboundKeepAlive {
+            keep_alive_id: reader.read_i64()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayPing {
+    pub id: i32,
+}
+
+impl Packet for PlayPing {
+    const KIND: PacketKind = PacketKind::PlayPing;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.id.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(PlayPing {
+            id: reader.read_i32()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayPong {
+    pub id: i32,
+}
+
+impl Packet for PlayPong {
+    const KIND: PacketKind = PacketKind::PlayPong;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.id.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(PlayPong {
+            id: reader.read_i32()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientboundChatMessage {
+    pub json: String,
+    pub position: u8, // 0: chat, 1: system message, 2: game info (action bar)
+    pub sender: u128,
+}
+
+impl Packet for ClientboundChatMessage {
+    const KIND: PacketKind = PacketKind::ClientboundChatMessage;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.json));
+        buf.push(self.position);
+        buf.extend_from_slice(&self.sender.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ClientboundChatMessage {
+            json: reader.read_string()?,
+            position: reader.read_u8()?,
+            sender: reader.read_uuid()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerboundChatMessage {
+    pub message: String, // at most 256 characters, commands start with '/'
+}
+
+impl Packet for ServerboundChatMessage {
+    const KIND: PacketKind = PacketKind::ServerboundChatMessage;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.message));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ServerboundChatMessage {
+            message: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerProperty {
+    pub name: String, // "textures" for the skin
+    pub value: String,
+    pub signature: Option<String>,
+}
+
+// a player of the tab list, as sent by the add action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerEntry {
+    pub uuid: u128,
+    pub name: String,
+    pub properties: Vec<PlayerProperty>,
+    pub game_mode: i32,
+    pub ping: i32,                    // milliseconds
+    pub display_name: Option<String>, // chat component json
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerInfoAction {
+    AddPlayer(Vec<PlayerEntry>),                    // 0
+    UpdateGameMode(Vec<(u128, i32)>),               // 1
+    UpdateLatency(Vec<(u128, i32)>),                // 2
+    UpdateDisplayName(Vec<(u128, Option<String>)>), // 3
+    RemovePlayer(Vec<u128>),                        // 4
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfo {
+    pub action: PlayerInfoAction,
+}
+
+fn optional_string_write(buf: &mut Vec<u8>, value: &Option<String>) {
+    match value {
+        Some(value) => {
+            buf.push(1);
+            buf.append(&mut string_write(value));
+        }
+        None => buf.push(0),
+    }
+}
+
+fn uuid_varints_write(buf: &mut Vec<u8>, action: i32, players: &[(u128, i32)]) {
+    buf.append(&mut varint_write(action));
+    buf.append(&mut varint_write(players.len() as i32));
+    for (uuid, value) in players {
+        buf.extend_from_slice(&uuid.to_be_bytes());
+        buf.append(&mut varint_write(*value));
+    }
+}
+
+impl Packet for PlayerInfo {
+    const KIND: PacketKind = PacketKind::PlayerInfo;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        match &self.action {
+            PlayerInfoAction::AddPlayer(players) => {
+                buf.append(&mut varint_write(0));
+                buf.append(&mut varint_write(players.len() as i32));
+                for player in players {
+                    buf.extend_from_slice(&player.uuid.to_be_bytes());
+                    buf.append(&mut string_write(&player.name));
+                    buf.append(&mut varint_write(player.properties.len() as i32));
+                    for property in &player.properties {
+                        buf.append(&mut string_write(&property.name));
+                        buf.append(&mut string_write(&property.value));
+                        optional_string_write(buf, &property.signature);
+                    }
+                    buf.append(&mut varint_write(player.game_mode));
+                    buf.append(&mut varint_write(player.ping));
+                    optional_string_write(buf, &player.display_name);
+                }
+            }
+            PlayerInfoAction::UpdateGameMode(players) => uuid_varints_write(buf, 1, players),
+            PlayerInfoAction::UpdateLatency(players) => uuid_varints_write(buf, 2, players),
+            PlayerInfoAction::UpdateDisplayName(players) => {
+                buf.append(&mut varint_write(3));
+                buf.append(&mut varint_write(players.len() as i32));
+                for (uuid, display_name) in players {
+                    buf.extend_from_slice(&uuid.to_be_bytes());
+                    optional_string_write(buf, display_name);
+                }
+            }
+            PlayerInfoAction::RemovePlayer(players) => {
+                buf.append(&mut varint_write(4));
+                buf.append(&mut varint_write(players.len() as i32));
+                for uuid in players {
+                    buf.extend_from_slice(&uuid.to_be_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let action = reader.read_varint()?;
+        let number_of_players = reader.read_varint()?;
+
+        let action = match action {
+            0 => {
+                let mut players = vec![];
+                for _ in 0..number_of_players {
+                    let uuid = reader.read_uuid()?;
+                    let name = reader.read_string()?;
+
+                    let number_of_properties = reader.read_varint()?;
+                    let mut properties = vec![];
+                    for _ in 0..number_of_properties {
+                        properties.push(PlayerProperty {
+                            name: reader.read_string()?,
+                            value: reader.read_string()?,
+                            signature: reader.read_optional_string()?,
+                        });
+                    }
+
+                    players.push(PlayerEntry {
+                        uuid,
+                        name,
+                        properties,
+                        game_mode: reader.read_varint()?,
+                        ping: reader.read_varint()?,
+                        display_name: reader.read_optional_string()?,
+                    });
+                }
+                PlayerInfoAction::AddPlayer(players)
+            }
+            1 | 2 => {
+                let mut players = vec![];
+                for _ in 0..number_of_players {
+                    players.push((reader.read_uuid()?, reader.read_varint()?));
+                }
+                if action == 1 {
+                    PlayerInfoAction::UpdateGameMode(players)
+                } else {
+                    PlayerInfoAction::UpdateLatency(players)
+                }
+            }
+            3 => {
+                let mut players = vec![];
+                for _ in 0..number_of_players {
+                    players.push((reader.read_uuid()?, reader.read_optional_string()?));
+                }
+                PlayerInfoAction::UpdateDisplayName(players)
+            }
+            4 => {
+                let mut players = vec![];
+                for _ in 0..number_of_players {
+                    players.push(reader.read_uuid()?);
+                }
+                PlayerInfoAction::RemovePlayer(players)
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown player info action {}", action),
+                ))
+            }
+        };
+
+        Ok(PlayerInfo { action })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disconnect {
+    pub reason: String, // chat component json
+}
+
+impl Packet for Disconnect {
+    const KIND: PacketKind = PacketKind::Disconnect;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.reason));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(Disconnect {
+            reason: reader.read_string()?,
+        })
+    }
+}
+
+// flags of Player Position And Look, a set bit means the field is relative to the current value
+pub const RELATIVE_X: u8 = 0x01;
+pub const RELATIVE_Y: u8 = 0x02;
+pub const RELATIVE_Z: u8 = 0x04;
+pub const RELATIVE_YAW: u8 = 0x08;
+pub const RELATIVE_PITCH: u8 = 0x10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPositionAndLook {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub flags: u8,
+    pub teleport_id: i32,
+    pub dismount_vehicle: bool, // since 1.17
+}
+
+impl Packet for PlayerPositionAndLook {
+    const KIND: PacketKind = PacketKind::PlayerPositionAndLook;
+
+    fn encode(&self, buf: &mut Vec<u8>, protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.x.to_be_bytes());
+        buf.extend_from_slice(&self.y.to_be_bytes());
+        buf.extend_from_slice(&self.z.to_be_bytes());
+        buf.extend_from_slice(&self.yaw.to_be_bytes());
+        buf.extend_from_slice(&self.pitch.to_be_bytes());
+        buf.push(self.flags);
+        buf.append(&mut varint_write(self.teleport_id));
+        if protocol >= V1_17 {
+            buf.append(&mut bool_write(self.dismount_vehicle));
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, protocol: i32) -> io::Result<Self> {
+        Ok(PlayerPositionAndLook {
+            x: reader.read_f64()?,
+            y: reader.read_f64()?,
+            z: reader.read_f64()?,
+            yaw: reader.read_f32()?,
+            pitch: reader.read_f32()?,
+            flags: reader.read_u8()?,
+            teleport_id: reader.read_varint()?,
+            dismount_vehicle: protocol >= V1_17 && reader.read_bool()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeleportConfirm {
+    pub teleport_id: i32,
+}
+
+impl Packet for TeleportConfirm {
+    const KIND: PacketKind = PacketKind::TeleportConfirm;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.teleport_id));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(TeleportConfirm {
+            teleport_id: reader.read_varint()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPosition {
+    pub x: f64,
+    pub y: f64, // feet
+    pub z: f64,
+    pub on_ground: bool,
+}
+
+impl Packet for PlayerPosition {
+    const KIND: PacketKind = PacketKind::PlayerPosition;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.x.to_be_bytes());
+        buf.extend_from_slice(&self.y.to_be_bytes());
+        buf.extend_from_slice(&self.z.to_be_bytes());
+        buf.append(&mut bool_write(self.on_ground));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(PlayerPosition {
+            x: reader.read_f64()?,
+            y: reader.read_f64()?,
+            z: reader.read_f64()?,
+            on_ground: reader.read_bool()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPositionAndRotation {
+    pub x: f64,
+    pub y: f64, // feet
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+impl Packet for PlayerPositionAndRotation {
+    const KIND: PacketKind = PacketKind::PlayerPositionAndRotation;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.x.to_be_bytes());
+        buf.extend_from_slice(&self.y.to_be_bytes());
+        buf.extend_from_slice(&self.z.to_be_bytes());
+        buf.extend_from_slice(&self.yaw.to_be_bytes());
+        buf.extend_from_slice(&self.pitch.to_be_bytes());
+        buf.append(&mut bool_write(self.on_ground));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(PlayerPositionAndRotation {
+            x: reader.read_f64()?,
+            y: reader.read_f64()?,
+            z: reader.read_f64()?,
+            yaw: reader.read_f32()?,
+            pitch: reader.read_f32()?,
+            on_ground: reader.read_bool()?,
+        })
+    }
+}
+
+fn nbt_read(reader: &mut PacketReader) -> io::Result<Tag> {
+    reader
+        .read_nbt()?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing NBT compound"))
+}
+
+fn nbt_write(buf: &mut Vec<u8>, tag: &Tag) -> io::Result<()> {
+    buf.append(&mut nbt::write_named("", tag)?);
+    Ok(())
+}
+
+// a VarInt count, then the strings
+fn strings_write(buf: &mut Vec<u8>, strings: &[String]) {
+    buf.append(&mut varint_write(strings.len() as i32));
+    for string in strings {
+        buf.append(&mut string_write(string));
+    }
+}
+
+fn strings_read(reader: &mut PacketReader) -> io::Result<Vec<String>> {
+    let count = reader.read_varint()?;
+    let mut strings = vec![];
+    for _ in 0..count {
+        strings.push(reader.read_string()?);
+    }
+    Ok(strings)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinGame {
+    pub entity_id: i32,
+    pub hardcore: bool,
+    pub game_mode: u8,
+    pub previous_game_mode: i8, // -1 when there is none
+    pub dimension_names: Vec<String>,
+    pub dimension_codec: Tag, // registries of dimension types and biomes
+    pub dimension_type: Tag,
+    pub dimension: String, // e.g. minecraft:overworld
+    pub hashed_seed: i64,
+    pub max_players: i32,
+    pub view_distance: i32,
+    pub simulation_distance: i32, // since 1.18
+    pub reduced_debug_info: bool,
+    pub enable_respawn_screen: bool,
+    pub debug: bool,
+    pub flat: bool,
+}
+
+impl Packet for JoinGame {
+    const KIND: PacketKind = PacketKind::JoinGame;
+
+    fn encode(&self, buf: &mut Vec<u8>, protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.entity_id.to_be_bytes());
+        buf.append(&mut bool_write(self.hardcore));
+        buf.push(self.game_mode);
+        buf.push(self.previous_game_mode as u8);
+        strings_write(buf, &self.dimension_names);
+        nbt_write(buf, &self.dimension_codec)?;
+        nbt_write(buf, &self.dimension_type)?;
+        buf.append(&mut string_write(&self.dimension));
+        buf.extend_from_slice(&self.hashed_seed.to_be_bytes());
+        buf.append(&mut varint_write(self.max_players));
+        buf.append(&mut varint_write(self.view_distance));
+        if protocol >= V1_18 {
+            buf.append(&mut varint_write(self.simulation_distance));
+        }
+        buf.append(&mut bool_write(self.reduced_debug_info));
+        buf.append(&mut bool_write(self.enable_respawn_screen));
+        buf.append(&mut bool_write(self.debug));
+        buf.append(&mut bool_write(self.flat));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, protocol: i32) -> io::Result<Self> {
+        let entity_id = reader.read_i32()?;
+        let hardcore = reader.read_bool()?;
+        let game_mode = reader.read_u8()?;
+        let previous_game_mode = reader.read_i8()?;
+
+        let dimension_names = strings_read(reader)?;
+
+        let dimension_codec = nbt_read(reader)?;
+        let dimension_type = nbt_read(reader)?;
+        let dimension = reader.read_string()?;
+        let hashed_seed = reader.read_i64()?;
+        let max_players = reader.read_varint()?;
+        let view_distance = reader.read_varint()?;
+        let simulation_distance = if protocol >= V1_18 {
+            reader.read_varint()?
+        } else {
+            view_distance
+        };
+
+        Ok(JoinGame {
+            entity_id,
+            hardcore,
+            game_mode,
+            previous_game_mode,
+            dimension_names,
+            dimension_codec,
+            dimension_type,
+            dimension,
+            hashed_seed,
+            max_players,
+            view_distance,
+            simulation_distance,
+            reduced_debug_info: reader.read_bool()?,
+            enable_respawn_screen: reader.read_bool()?,
+            debug: reader.read_bool()?,
+            flat: reader.read_bool()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Respawn {
+    pub dimension_type: Tag,
+    pub dimension: String,
+    pub hashed_seed: i64,
+    pub game_mode: u8,
+    pub previous_game_mode: i8,
+    pub debug: bool,
+    pub flat: bool,
+    pub copy_metadata: bool,
+}
+
+impl Packet for Respawn {
+    const KIND: PacketKind = PacketKind::Respawn;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        nbt_write(buf, &self.dimension_type)?;
+        buf.append(&mut string_write(&self.dimension));
+        buf.extend_from_slice(&self.hashed_seed.to_be_bytes());
+        buf.push(self.game_mode);
+        buf.push(self.previous_game_mode as u8);
+        buf.append(&mut bool_write(self.debug));
+        buf.append(&mut bool_write(self.flat));
+        buf.append(&mut bool_write(self.copy_metadata));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(Respawn {
+            dimension_type: nbt_read(reader)?,
+            dimension: reader.read_string()?,
+            hashed_seed: reader.read_i64()?,
+            game_mode: reader.read_u8()?,
+            previous_game_mode: reader.read_i8()?,
+            debug: reader.read_bool()?,
+            flat: reader.read_bool()?,
+            copy_metadata: reader.read_bool()?,
+        })
+    }
+}
+
+// reasons of Change Game State
+pub const BEGIN_RAINING: u8 = 1;
+pub const END_RAINING: u8 = 2;
+pub const CHANGE_GAME_MODE: u8 = 3;
+pub const WIN_GAME: u8 = 4;
+pub const ENABLE_RESPAWN_SCREEN: u8 = 11;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeGameState {
+    pub reason: u8,
+    pub value: f32,
+}
+
+impl Packet for ChangeGameState {
+    const KIND: PacketKind = PacketKind::ChangeGameState;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.push(self.reason);
+        buf.extend_from_slice(&self.value.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ChangeGameState {
+            reason: reader.read_u8()?,
+            value: reader.read_f32()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerDifficulty {
+    pub difficulty: u8, // 0: peaceful, 1: easy, 2: normal, 3: hard
+    pub locked: bool,
+}
+
+impl Packet for ServerDifficulty {
+    const KIND: PacketKind = PacketKind::ServerDifficulty;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.push(self.difficulty);
+        buf.append(&mut bool_write(self.locked));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ServerDifficulty {
+            difficulty: reader.read_u8()?,
+            locked: reader.read_bool()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateHealth {
+    pub health: f32, // 0 or less means dead, 20 is full
+    pub food: i32,   // 0 to 20
+    pub saturation: f32,
+}
+
+impl Packet for UpdateHealth {
+    const KIND: PacketKind = PacketKind::UpdateHealth;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.health.to_be_bytes());
+        buf.append(&mut varint_write(self.food));
+        buf.extend_from_slice(&self.saturation.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(UpdateHealth {
+            health: reader.read_f32()?,
+            food: reader.read_varint()?,
+            saturation: reader.read_f32()?,
+        })
+    }
+}
+
+// 1.16.5 sends the three events as one packet with an event id, 1.17 split it in three and
+// only the death one has this kind there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CombatEvent {
+    EnterCombat, // 0
+    EndCombat {
+        duration: i32, // ticks
+        entity_id: i32,
+    }, // 1
+    EntityDead {
+        player_id: i32,
+        entity_id: i32,  // the killer, -1 when there is none
+        message: String, // chat component json
+    }, // 2
+}
+
+impl Packet for CombatEvent {
+    const KIND: PacketKind = PacketKind::CombatEvent;
+
+    fn encode(&self, buf: &mut Vec<u8>, protocol: i32) -> io::Result<()> {
+        if protocol < V1_17 {
+            buf.append(&mut varint_write(match self {
+                CombatEvent::EnterCombat => 0,
+                CombatEvent::EndCombat { .. } => 1,
+                CombatEvent::EntityDead { .. } => 2,
+            }));
+        }
+        match self {
+            CombatEvent::EnterCombat => {}
+            CombatEvent::EndCombat {
+                duration,
+                entity_id,
+            } => {
+                buf.append(&mut varint_write(*duration));
+                buf.extend_from_slice(&entity_id.to_be_bytes());
+            }
+            CombatEvent::EntityDead {
+                player_id,
+                entity_id,
+                message,
+            } => {
+                buf.append(&mut varint_write(*player_id));
+                buf.extend_from_slice(&entity_id.to_be_bytes());
+                buf.append(&mut string_write(message));
+            }
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, protocol: i32) -> io::Result<Self> {
+        let event = if protocol < V1_17 {
+            reader.read_varint()?
+        } else {
+            2
+        };
+        match event {
+            0 => Ok(CombatEvent::EnterCombat),
+            1 => Ok(CombatEvent::EndCombat {
+                duration: reader.read_varint()?,
+                entity_id: reader.read_i32()?,
+            }),
+            2 => Ok(CombatEvent::EntityDead {
+                player_id: reader.read_varint()?,
+                entity_id: reader.read_i32()?,
+                message: reader.read_string()?,
+            }),
+            event => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown combat event {}", event),
+            )),
+        }
+    }
+}
+
+pub const PERFORM_RESPAWN: i32 = 0;
+pub const REQUEST_STATS: i32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientStatus {
+    pub action: i32,
+}
+
+impl Packet for ClientStatus {
+    const KIND: PacketKind = PacketKind::ClientStatus;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.action));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ClientStatus {
+            action: reader.read_varint()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerListHeaderAndFooter {
+    pub header: String, // chat component json, an empty text removes it
+    pub footer: String,
+}
+
+impl Packet for PlayerListHeaderAndFooter {
+    const KIND: PacketKind = PacketKind::PlayerListHeaderAndFooter;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.header));
+        buf.append(&mut string_write(&self.footer));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(PlayerListHeaderAndFooter {
+            header: reader.read_string()?,
+            footer: reader.read_string()?,
+        })
+    }
+}
+
+// 1.16.5 sends everything about titles in one packet, 1.17 split it into the packets below
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TitleAction {
+    SetTitle(String),     // 0, chat component json
+    SetSubtitle(String),  // 1
+    SetActionBar(String), // 2
+    SetTimes {
+        fade_in: i32, // ticks
+        stay: i32,
+        fade_out: i32,
+    }, // 3
+    Hide,                 // 4
+    Reset,                // 5, also forgets the subtitle and the times
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Title {
+    pub action: TitleAction,
+}
+
+impl Packet for Title {
+    const KIND: PacketKind = PacketKind::Title;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        match &self.action {
+            TitleAction::SetTitle(text) => {
+                buf.append(&mut varint_write(0));
+                buf.append(&mut string_write(text));
+            }
+            TitleAction::SetSubtitle(text) => {
+                buf.append(&mut varint_write(1));
+                buf.append(&mut string_write(text));
+            }
+            TitleAction::SetActionBar(text) => {
+                buf.append(&mut varint_write(2));
+                buf.append(&mut string_write(text));
+            }
+            TitleAction::SetTimes {
+                fade_in,
+                stay,
+                fade_out,
+            } => {
+                buf.append(&mut varint_write(3));
+                buf.extend_from_slice(&fade_in.to_be_bytes());
+                buf.extend_from_slice(&stay.to_be_bytes());
+                buf.extend_from_slice(&fade_out.to_be_bytes());
+            }
+            TitleAction::Hide => buf.append(&mut varint_write(4)),
+            TitleAction::Reset => buf.append(&mut varint_write(5)),
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let action = match reader.read_varint()? {
+            0 => TitleAction::SetTitle(reader.read_string()?),
+            1 => TitleAction::SetSubtitle(reader.read_string()?),
+            2 => TitleAction::SetActionBar(reader.read_string()?),
+            3 => TitleAction::SetTimes {
+                fade_in: reader.read_i32()?,
+                stay: reader.read_i32()?,
+                fade_out: reader.read_i32()?,
+            },
+            4 => TitleAction::Hide,
+            5 => TitleAction::Reset,
+            action => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown title action {}", action),
+                ))
+            }
+        };
+
+        Ok(Title { action })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetTitleText {
+    pub text: String, // chat component json
+}
+
+impl Packet for SetTitleText {
+    const KIND: PacketKind = PacketKind::SetTitleText;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.text));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(SetTitleText {
+            text: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetTitleSubtitle {
+    pub text: String, // chat component json
+}
+
+impl Packet for SetTitleSubtitle {
+    const KIND: PacketKind = PacketKind::SetTitleSubtitle;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.text));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(SetTitleSubtitle {
+            text: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionBar {
+    pub text: String, // chat component json
+}
+
+impl Packet for ActionBar {
+    const KIND: PacketKind = PacketKind::ActionBar;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.text));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ActionBar {
+            text: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetTitleTimes {
+    pub fade_in: i32, // ticks
+    pub stay: i32,
+    pub fade_out: i32,
+}
+
+impl Packet for SetTitleTimes {
+    const KIND: PacketKind = PacketKind::SetTitleTimes;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.fade_in.to_be_bytes());
+        buf.extend_from_slice(&self.stay.to_be_bytes());
+        buf.extend_from_slice(&self.fade_out.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(SetTitleTimes {
+            fade_in: reader.read_i32()?,
+            stay: reader.read_i32()?,
+            fade_out: reader.read_i32()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearTitles {
+    pub reset: bool, // also forget the subtitle and the times
+}
+
+impl Packet for ClearTitles {
+    const KIND: PacketKind = PacketKind::ClearTitles;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut bool_write(self.reset));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ClearTitles {
+            reset: reader.read_bool()?,
+        })
+    }
+}
+
+// the split packets as the actions of the 1.16.5 one, so both are handled the same way
+impl From<SetTitleText> for TitleAction {
+    fn from(packet: SetTitleText) -> Self {
+        TitleAction::SetTitle(packet.text)
+    }
+}
+
+impl From<SetTitleSubtitle> for TitleAction {
+    fn from(packet: SetTitleSubtitle) -> Self {
+        TitleAction::SetSubtitle(packet.text)
+    }
+}
+
+impl From<ActionBar> for TitleAction {
+    fn from(packet: ActionBar) -> Self {
+        TitleAction::SetActionBar(packet.text)
+    }
+}
+
+impl From<SetTitleTimes> for TitleAction {
+    fn from(packet: SetTitleTimes) -> Self {
+        TitleAction::SetTimes {
+            fade_in: packet.fade_in,
+            stay: packet.stay,
+            fade_out: packet.fade_out,
+        }
+    }
+}
+
+impl From<ClearTitles> for TitleAction {
+    fn from(packet: ClearTitles) -> Self {
+        if packet.reset {
+            TitleAction::Reset
+        } else {
+            TitleAction::Hide
+        }
+    }
+}
+
+pub const BOSS_BAR_DARKEN_SKY: u8 = 0x01;
+pub const BOSS_BAR_DRAGON_BAR: u8 = 0x02; // plays the end music
+pub const BOSS_BAR_CREATE_FOG: u8 = 0x04;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BossBarAction {
+    Add {
+        title: String, // chat component json
+        health: f32,   // 0 to 1
+        color: i32,    // 0: pink, 1: blue, 2: red, 3: green, 4: yellow, 5: purple, 6: white
+        division: i32, // 0: none, 1: 6 notches, 2: 10, 3: 12, 4: 20
+        flags: u8,
+    }, // 0
+    Remove,              // 1
+    UpdateHealth(f32),   // 2
+    UpdateTitle(String), // 3
+    UpdateStyle {
+        color: i32,
+        division: i32,
+    }, // 4
+    UpdateFlags(u8),     // 5
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BossBar {
+    pub uuid: u128,
+    pub action: BossBarAction,
+}
+
+impl Packet for BossBar {
+    const KIND: PacketKind = PacketKind::BossBar;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.uuid.to_be_bytes());
+        match &self.action {
+            BossBarAction::Add {
+                title,
+                health,
+                color,
+                division,
+                flags,
+            } => {
+                buf.append(&mut varint_write(0));
+                buf.append(&mut string_write(title));
+                buf.extend_from_slice(&health.to_be_bytes());
+                buf.append(&mut varint_write(*color));
+                buf.append(&mut varint_write(*division));
+                buf.push(*flags);
+            }
+            BossBarAction::Remove => buf.append(&mut varint_write(1)),
+            BossBarAction::UpdateHealth(health) => {
+                buf.append(&mut varint_write(2));
+                buf.extend_from_slice(&health.to_be_bytes());
+            }
+            BossBarAction::UpdateTitle(title) => {
+                buf.append(&mut varint_write(3));
+                buf.append(&mut string_write(title));
+            }
+            BossBarAction::UpdateStyle { color, division } => {
+                buf.append(&mut varint_write(4));
+                buf.append(&mut varint_write(*color));
+                buf.append(&mut varint_write(*division));
+            }
+            BossBarAction::UpdateFlags(flags) => {
+                buf.append(&mut varint_write(5));
+                buf.push(*flags);
+            }
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let uuid = reader.read_uuid()?;
+        let action = match reader.read_varint()? {
+            0 => BossBarAction::Add {
+                title: reader.read_string()?,
+                health: reader.read_f32()?,
+                color: reader.read_varint()?,
+                division: reader.read_varint()?,
+                flags: reader.read_u8()?,
+            },
+            1 => BossBarAction::Remove,
+            2 => BossBarAction::UpdateHealth(reader.read_f32()?),
+            3 => BossBarAction::UpdateTitle(reader.read_string()?),
+            4 => BossBarAction::UpdateStyle {
+                color: reader.read_varint()?,
+                division: reader.read_varint()?,
+            },
+            5 => BossBarAction::UpdateFlags(reader.read_u8()?),
+            action => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown boss bar action {}", action),
+                ))
+            }
+        };
+
+        Ok(BossBar { uuid, action })
+    }
+}
+
+pub const DISPLAY_LIST: i8 = 0;
+pub const DISPLAY_SIDEBAR: i8 = 1;
+pub const DISPLAY_BELOW_NAME: i8 = 2;
+// 3 to 18: the sidebar seen by the members of teams of color 0 to 15
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayScoreboard {
+    pub position: i8,
+    pub score_name: String, // the objective, empty to show none
+}
+
+impl Packet for DisplayScoreboard {
+    const KIND: PacketKind = PacketKind::DisplayScoreboard;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.push(self.position as u8);
+        buf.append(&mut string_write(&self.score_name));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(DisplayScoreboard {
+            position: reader.read_i8()?,
+            score_name: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectiveAction {
+    Create {
+        display_name: String, // chat component json
+        render_type: i32,     // 0: integer, 1: hearts
+    }, // 0
+    Remove, // 1
+    Update {
+        display_name: String,
+        render_type: i32,
+    }, // 2
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreboardObjective {
+    pub name: String,
+    pub action: ObjectiveAction,
+}
+
+impl Packet for ScoreboardObjective {
+    const KIND: PacketKind = PacketKind::ScoreboardObjective;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.name));
+        let mut display = |mode: u8, display_name: &str, render_type: i32| {
+            buf.push(mode);
+            buf.append(&mut string_write(display_name));
+            buf.append(&mut varint_write(render_type));
+        };
+        match &self.action {
+            ObjectiveAction::Create {
+                display_name,
+                render_type,
+            } => display(0, display_name, *render_type),
+            ObjectiveAction::Update {
+                display_name,
+                render_type,
+            } => display(2, display_name, *render_type),
+            ObjectiveAction::Remove => buf.push(1),
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let name = reader.read_string()?;
+        let action = match reader.read_i8()? {
+            0 => ObjectiveAction::Create {
+                display_name: reader.read_string()?,
+                render_type: reader.read_varint()?,
+            },
+            1 => ObjectiveAction::Remove,
+            2 => ObjectiveAction::Update {
+                display_name: reader.read_string()?,
+                render_type: reader.read_varint()?,
+            },
+            mode => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown scoreboard objective mode {}", mode),
+                ))
+            }
+        };
+
+        Ok(ScoreboardObjective { name, action })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateScore {
+    pub entity_name: String,    // a player name, or an entity uuid
+    pub objective_name: String, // empty with a removal to remove the entity from all of them
+    pub value: Option<i32>,     // None removes the score
+}
+
+impl Packet for UpdateScore {
+    const KIND: PacketKind = PacketKind::UpdateScore;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.entity_name));
+        buf.push(if self.value.is_some() { 0 } else { 1 });
+        buf.append(&mut string_write(&self.objective_name));
+        if let Some(value) = self.value {
+            buf.append(&mut varint_write(value));
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let entity_name = reader.read_string()?;
+        let action = reader.read_i8()?;
+        let objective_name = reader.read_string()?;
+        let value = match action {
+            0 => Some(reader.read_varint()?),
+            1 => None,
+            action => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown update score action {}", action),
+                ))
+            }
+        };
+
+        Ok(UpdateScore {
+            entity_name,
+            objective_name,
+            value,
+        })
+    }
+}
+
+pub const TEAM_ALLOW_FRIENDLY_FIRE: u8 = 0x01;
+pub const TEAM_SEE_INVISIBLE_TEAMMATES: u8 = 0x02;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamInfo {
+    pub display_name: String, // chat component json
+    pub friendly_flags: u8,
+    pub name_tag_visibility: String, // always, hideForOtherTeams, hideForOwnTeam, never
+    pub collision_rule: String,      // always, pushOtherTeams, pushOwnTeam, never
+    pub color: i32,                  // 0 to 15 like the § codes, 21 for none
+    pub prefix: String,              // chat component json
+    pub suffix: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TeamAction {
+    Create(TeamInfo, Vec<String>), // 0, with the entities
+    Remove,                        // 1
+    UpdateInfo(TeamInfo),          // 2
+    AddEntities(Vec<String>),      // 3
+    RemoveEntities(Vec<String>),   // 4
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Teams {
+    pub name: String,
+    pub action: TeamAction,
+}
+
+fn team_info_write(buf: &mut Vec<u8>, info: &TeamInfo) {
+    buf.append(&mut string_write(&info.display_name));
+    buf.push(info.friendly_flags);
+    buf.append(&mut string_write(&info.name_tag_visibility));
+    buf.append(&mut string_write(&info.collision_rule));
+    buf.append(&mut varint_write(info.color));
+    buf.append(&mut string_write(&info.prefix));
+    buf.append(&mut string_write(&info.suffix));
+}
+
+fn team_info_read(reader: &mut PacketReader) -> io::Result<TeamInfo> {
+    Ok(TeamInfo {
+        display_name: reader.read_string()?,
+        friendly_flags: reader.read_u8()?,
+        name_tag_visibility: reader.read_string()?,
+        collision_rule: reader.read_string()?,
+        color: reader.read_varint()?,
+        prefix: reader.read_string()?,
+        suffix: reader.read_string()?,
+    })
+}
+
+impl Packet for Teams {
+    const KIND: PacketKind = PacketKind::Teams;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.name));
+        match &self.action {
+            TeamAction::Create(info, entities) => {
+                buf.push(0);
+                team_info_write(buf, info);
+                strings_write(buf, entities);
+            }
+            TeamAction::Remove => buf.push(1),
+            TeamAction::UpdateInfo(info) => {
+                buf.push(2);
+                team_info_write(buf, info);
+            }
+            TeamAction::AddEntities(entities) => {
+                buf.push(3);
+                strings_write(buf, entities);
+            }
+            TeamAction::RemoveEntities(entities) => {
+                buf.push(4);
+                strings_write(buf, entities);
+            }
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let name = reader.read_string()?;
+        let action = match reader.read_i8()? {
+            0 => TeamAction::Create(team_info_read(reader)?, strings_read(reader)?),
+            1 => TeamAction::Remove,
+            2 => TeamAction::UpdateInfo(team_info_read(reader)?),
+            3 => TeamAction::AddEntities(strings_read(reader)?),
+            4 => TeamAction::RemoveEntities(strings_read(reader)?),
+            mode => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown teams mode {}", mode),
+                ))
+            }
+        };
+
+        Ok(Teams { name, action })
+    }
+}
+
+// The properties of an argument parser, which ones a parser has depends on its identifier
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParserProperties {
+    None,
+    Double { min: Option<f64>, max: Option<f64> }, // brigadier:double
+    Float { min: Option<f32>, max: Option<f32> },  // brigadier:float
+    Integer { min: Option<i32>, max: Option<i32> }, // brigadier:integer
+    Long { min: Option<i64>, max: Option<i64> },   // brigadier:long
+    String(i32), // brigadier:string, 0: single word, 1: quotable phrase, 2: greedy phrase
+    Flags(u8),   // minecraft:entity (0x01 single, 0x02 players only), minecraft:score_holder
+    Range { decimals: bool }, // minecraft:range
+    Registry(String), // minecraft:resource and minecraft:resource_or_tag, since 1.18.2
+}
+
+pub const GREEDY_PHRASE: i32 = 2;
+
+fn min_max_read<'a, T>(
+    reader: &mut PacketReader<'a>,
+    read: fn(&mut PacketReader<'a>) -> io::Result<T>,
+) -> io::Result<(Option<T>, Option<T>)> {
+    let flags = reader.read_u8()?;
+    let min = if flags & 0x01 != 0 {
+        Some(read(reader)?)
+    } else {
+        None
+    };
+    let max = if flags & 0x02 != 0 {
+        Some(read(reader)?)
+    } else {
+        None
+    };
+    Ok((min, max))
+}
+
+fn min_max_write<T: Copy, const N: usize>(
+    buf: &mut Vec<u8>,
+    min: Option<T>,
+    max: Option<T>,
+    to_bytes: fn(T) -> [u8; N],
+) {
+    buf.push(min.is_some() as u8 | (max.is_some() as u8) << 1);
+    for value in [min, max].into_iter().flatten() {
+        buf.extend_from_slice(&to_bytes(value));
+    }
+}
+
+impl ParserProperties {
+    fn read(reader: &mut PacketReader, parser: &str) -> io::Result<ParserProperties> {
+        Ok(match parser {
+            "brigadier:double" => {
+                let (min, max) = min_max_read(reader, PacketReader::read_f64)?;
+                ParserProperties::Double { min, max }
+            }
+            "brigadier:float" => {
+                let (min, max) = min_max_read(reader, PacketReader::read_f32)?;
+                ParserProperties::Float { min, max }
+            }
+            "brigadier:integer" => {
+                let (min, max) = min_max_read(reader, PacketReader::read_i32)?;
+                ParserProperties::Integer { min, max }
+            }
+            "brigadier:long" => {
+                let (min, max) = min_max_read(reader, PacketReader::read_i64)?;
+                ParserProperties::Long { min, max }
+            }
+            "brigadier:string" => ParserProperties::String(reader.read_varint()?),
+            "minecraft:entity" | "minecraft:score_holder" => {
+                ParserProperties::Flags(reader.read_u8()?)
+            }
+            "minecraft:range" => ParserProperties::Range {
+                decimals: reader.read_bool()?,
+            },
+            "minecraft:resource" | "minecraft:resource_or_tag" => {
+                ParserProperties::Registry(reader.read_string()?)
+            }
+            _ => ParserProperties::None,
+        })
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) {
+        match self {
+            ParserProperties::None => {}
+            ParserProperties::Double { min, max } => {
+                min_max_write(buf, *min, *max, f64::to_be_bytes)
+            }
+            ParserProperties::Float { min, max } => {
+                min_max_write(buf, *min, *max, f32::to_be_bytes)
+            }
+            ParserProperties::Integer { min, max } => {
+                min_max_write(buf, *min, *max, i32::to_be_bytes)
+            }
+            ParserProperties::Long { min, max } => min_max_write(buf, *min, *max, i64::to_be_bytes),
+            ParserProperties::String(kind) => buf.append(&mut varint_write(*kind)),
+            ParserProperties::Flags(flags) => buf.push(*flags),
+            ParserProperties::Range { decimals } => buf.append(&mut bool_write(*decimals)),
+            ParserProperties::Registry(registry) => buf.append(&mut string_write(registry)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandNodeKind {
+    Root,
+    Literal(String),
+    Argument {
+        name: String,
+        parser: String, // e.g. brigadier:integer, minecraft:entity
+        properties: ParserProperties,
+        suggestions: Option<String>, // e.g. minecraft:ask_server
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandNode {
+    pub kind: CommandNodeKind,
+    pub executable: bool,
+    pub children: Vec<i32>, // indices into the nodes of the packet
+    pub redirect: Option<i32>,
+}
+
+const NODE_TYPE: u8 = 0x03;
+const NODE_EXECUTABLE: u8 = 0x04;
+const NODE_REDIRECT: u8 = 0x08;
+const NODE_SUGGESTIONS: u8 = 0x10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclareCommands {
+    pub nodes: Vec<CommandNode>,
+    pub root_index: i32,
+}
+
+impl Packet for DeclareCommands {
+    const KIND: PacketKind = PacketKind::DeclareCommands;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.nodes.len() as i32));
+        for node in &self.nodes {
+            let mut flags = match node.kind {
+                CommandNodeKind::Root => 0,
+                CommandNodeKind::Literal(_) => 1,
+                CommandNodeKind::Argument { .. } => 2,
+            };
+            if node.executable {
+                flags |= NODE_EXECUTABLE;
+            }
+            if node.redirect.is_some() {
+                flags |= NODE_REDIRECT;
+            }
+            if let CommandNodeKind::Argument {
+                suggestions: Some(_),
+                ..
+            } = node.kind
+            {
+                flags |= NODE_SUGGESTIONS;
+            }
+            buf.push(flags);
+
+            buf.append(&mut varint_write(node.children.len() as i32));
+            for child in &node.children {
+                buf.append(&mut varint_write(*child));
+            }
+            if let Some(redirect) = node.redirect {
+                buf.append(&mut varint_write(redirect));
+            }
+
+            match &node.kind {
+                CommandNodeKind::Root => {}
+                CommandNodeKind::Literal(name) => buf.append(&mut string_write(name)),
+                CommandNodeKind::Argument {
+                    name,
+                    parser,
+                    properties,
+                    suggestions,
+                } => {
+                    buf.append(&mut string_write(name));
+                    buf.append(&mut string_write(parser));
+                    properties.write(buf);
+                    if let Some(suggestions) = suggestions {
+                        buf.append(&mut string_write(suggestions));
+                    }
+                }
+            }
+        }
+        buf.append(&mut varint_write(self.root_index));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let count = reader.read_varint()?;
+        let mut nodes = vec![];
+        for _ in 0..count {
+            let flags = reader.read_u8()?;
+
+            let child_count = reader.read_varint()?;
+            let mut children = vec![];
+            for _ in 0..child_count {
+                children.push(reader.read_varint()?);
+            }
+            let redirect = if flags & NODE_REDIRECT != 0 {
+                Some(reader.read_varint()?)
+            } else {
+                None
+            };
+
+            let kind = match flags & NODE_TYPE {
+                0 => CommandNodeKind::Root,
+                1 => CommandNodeKind::Literal(reader.read_string()?),
+                2 => {
+                    let name = reader.read_string()?;
+                    let parser = reader.read_string()?;
+                    let properties = ParserProperties::read(reader, &parser)?;
+                    let suggestions = if flags & NODE_SUGGESTIONS != 0 {
+                        Some(reader.read_string()?)
+                    } else {
+                        None
+                    };
+                    CommandNodeKind::Argument {
+                        name,
+                        parser,
+                        properties,
+                        suggestions,
+                    }
+                }
+                node_type => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Unknown command node type {}", node_type),
+                    ))
+                }
+            };
+
+            nodes.push(CommandNode {
+                kind,
+                executable: flags & NODE_EXECUTABLE != 0,
+                children,
+                redirect,
+            });
+        }
+
+        Ok(DeclareCommands {
+            nodes,
+            root_index: reader.read_varint()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerboundTabComplete {
+    pub transaction_id: i32,
+    pub text: String, // everything before the cursor, with the leading '/'
+}
+
+impl Packet for ServerboundTabComplete {
+    const KIND: PacketKind = PacketKind::ServerboundTabComplete;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.transaction_id));
+        buf.append(&mut string_write(&self.text));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ServerboundTabComplete {
+            transaction_id: reader.read_varint()?,
+            text: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabCompleteMatch {
+    pub text: String,
+    pub tooltip: Option<String>, // chat component json
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientboundTabComplete {
+    pub transaction_id: i32,
+    pub start: i32, // the part of the text the matches replace, in characters
+    pub length: i32,
+    pub matches: Vec<TabCompleteMatch>,
+}
+
+impl Packet for ClientboundTabComplete {
+    const KIND: PacketKind = PacketKind::ClientboundTabComplete;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut varint_write(self.transaction_id));
+        buf.append(&mut varint_write(self.start));
+        buf.append(&mut varint_write(self.length));
+        buf.append(&mut varint_write(self.matches.len() as i32));
+        for suggestion in &self.matches {
+            buf.append(&mut string_write(&suggestion.text));
+            optional_string_write(buf, &suggestion.tooltip);
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        let transaction_id = reader.read_varint()?;
+        let start = reader.read_varint()?;
+        let length = reader.read_varint()?;
+        let count = reader.read_varint()?;
+        let mut matches = vec![];
+        for _ in 0..count {
+            matches.push(TabCompleteMatch {
+                text: reader.read_string()?,
+                tooltip: reader.read_optional_string()?,
+            });
+        }
+
+        Ok(ClientboundTabComplete {
+            transaction_id,
+            start,
+            length,
+            matches,
+        })
+    }
+}
+
+pub const CHAT_ENABLED: i32 = 0;
+pub const CHAT_COMMANDS_ONLY: i32 = 1;
+pub const CHAT_HIDDEN: i32 = 2;
+
+pub const MAIN_HAND_LEFT: i32 = 0;
+pub const MAIN_HAND_RIGHT: i32 = 1;
+
+// cape, jacket, left and right sleeves and pant legs, hat
+pub const ALL_SKIN_PARTS: u8 = 0x7F;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSettings {
+    pub locale: String, // e.g. en_us
+    pub view_distance: i8,
+    pub chat_mode: i32,
+    pub chat_colors: bool,
+    pub displayed_skin_parts: u8,
+    pub main_hand: i32,
+    pub text_filtering: bool,        // since 1.17
+    pub allow_server_listings: bool, // since 1.18
+}
+
+impl Packet for ClientSettings {
+    const KIND: PacketKind = PacketKind::ClientSettings;
+
+    fn encode(&self, buf: &mut Vec<u8>, protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.locale));
+        buf.push(self.view_distance as u8);
+        buf.append(&mut varint_write(self.chat_mode));
+        buf.append(&mut bool_write(self.chat_colors));
+        buf.push(self.displayed_skin_parts);
+        buf.append(&mut varint_write(self.main_hand));
+        if protocol >= V1_17 {
+            buf.append(&mut bool_write(self.text_filtering));
+        }
+        if protocol >= V1_18 {
+            buf.append(&mut bool_write(self.allow_server_listings));
+        }
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, protocol: i32) -> io::Result<Self> {
+        Ok(ClientSettings {
+            locale: reader.read_string()?,
+            view_distance: reader.read_i8()?,
+            chat_mode: reader.read_varint()?,
+            chat_colors: reader.read_bool()?,
+            displayed_skin_parts: reader.read_u8()?,
+            main_hand: reader.read_varint()?,
+            text_filtering: protocol >= V1_17 && reader.read_bool()?,
+            allow_server_listings: if protocol >= V1_18 {
+                reader.read_bool()?
+            } else {
+                true
+            },
+        })
+    }
+}
+
+// the data is whatever the channel defines, up to the end of the packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientboundPluginMessage {
+    pub channel: String,
+    pub data: Vec<u8>,
+}
+
+impl Packet for ClientboundPluginMessage {
+    const KIND: PacketKind = PacketKind::ClientboundPluginMessage;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.channel));
+        buf.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ClientboundPluginMessage {
+            channel: reader.read_string()?,
+            data: reader.rest().to_vec(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerboundPluginMessage {
+    pub channel: String,
+    pub data: Vec<u8>,
+}
+
+impl Packet for ServerboundPluginMessage {
+    const KIND: PacketKind = PacketKind::ServerboundPluginMessage;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.channel));
+        buf.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(ServerboundPluginMessage {
+            channel: reader.read_string()?,
+            data: reader.rest().to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_alive_and_ping_round_trip() {
+        let keep_alive = ClientboundKeepAlive {
+            keep_alive_id: 1_700_000_000_123,
+        };
+        assert_eq!(keep_alive, round_trip(&keep_alive));
+
+        let answer = ServerboundKeepAlive {
+            keep_alive_id: keep_alive.keep_alive_id,
+        };
+        assert_eq!(answer, round_trip(&answer));
+
+        assert_eq!(PlayPing { id: 7 }, round_trip(&PlayPing { id: 7 }));
+        assert_eq!(PlayPong { id: -7 }, round_trip(&PlayPong { id: -7 }));
+    }
+
+    #[test]
+    fn test_chat_round_trip() {
+        let incoming = ClientboundChatMessage {
+            json: r#"{"translate":"chat.type.text","with":["Steve","hi"]}"#.to_string(),
+            position: 0,
+            sender: 42,
+        };
+        assert_eq!(incoming, round_trip(&incoming));
+
+        let outgoing = ServerboundChatMessage {
+            message: "hello".to_string(),
+        };
+        assert_eq!(
+            vec![0x05, b'h', b'e', b'l', b'l', b'o'],
+            outgoing.to_bytes(758).unwrap()
+        );
+        assert_eq!(outgoing, round_trip(&outgoing));
+
+        let disconnect = Disconnect {
+            reason: r#"{"text":"Server closed"}"#.to_string(),
+        };
+        assert_eq!(disconnect, round_trip(&disconnect));
+    }
+
+    #[test]
+    fn test_player_info_round_trip() {
+        let add = PlayerInfo {
+            action: PlayerInfoAction::AddPlayer(vec![
+                PlayerEntry {
+                    uuid: 1,
+                    name: "Steve".to_string(),
+                    properties: vec![],
+                    game_mode: 0,
+                    ping: 12,
+                    display_name: None,
+                },
+                PlayerEntry {
+                    uuid: 2,
+                    name: "Alex".to_string(),
+                    properties: vec![
+                        PlayerProperty {
+                            name: "textures".to_string(),
+                            value: "e30=".to_string(),
+                            signature: Some("c2ln".to_string()),
+                        },
+                        PlayerProperty {
+                            name: "extra".to_string(),
+                            value: "".to_string(),
+                            signature: None,
+                        },
+                    ],
+                    game_mode: 1,
+                    ping: 250,
+                    display_name: Some(r#"{"text":"[Admin] Alex"}"#.to_string()),
+                },
+            ]),
+        };
+        assert_eq!(add, round_trip(&add));
+
+        for action in [
+            PlayerInfoAction::UpdateGameMode(vec![(1, 3)]),
+            PlayerInfoAction::UpdateLatency(vec![(1, 40), (2, 1000)]),
+            PlayerInfoAction::UpdateDisplayName(vec![(1, None), (2, Some("{}".to_string()))]),
+            PlayerInfoAction::RemovePlayer(vec![1, 2]),
+        ] {
+            let packet = PlayerInfo { action };
+            assert_eq!(packet, round_trip(&packet));
+        }
+
+        let header_footer = PlayerListHeaderAndFooter {
+            header: r#"{"text":"Welcome\n","color":"gold"}"#.to_string(),
+            footer: r#"{"text":""}"#.to_string(),
+        };
+        assert_eq!(header_footer, round_trip(&header_footer));
+    }
+
+    #[test]
+    fn test_position_round_trip() {
+        let teleport = PlayerPositionAndLook {
+            x: 0.5,
+            y: 64.0,
+            z: -12.25,
+            yaw: 90.0,
+            pitch: -15.5,
+            flags: RELATIVE_YAW | RELATIVE_PITCH,
+            teleport_id: 3,
+            dismount_vehicle: true,
+        };
+        assert_eq!(teleport, round_trip(&teleport));
+        assert_eq!(
+            PlayerPositionAndLook {
+                dismount_vehicle: false,
+                ..teleport.clone()
+            },
+            round_trip_version(&teleport, 754)
+        );
+        assert_eq!(34, teleport.to_bytes(754).unwrap().len());
+        assert_eq!(35, teleport.to_bytes(758).unwrap().len());
+
+        let confirm = TeleportConfirm { teleport_id: 3 };
+        assert_eq!(confirm, round_trip(&confirm));
+
+        let position = PlayerPosition {
+            x: 0.5,
+            y: 64.0,
+            z: -12.25,
+            on_ground: true,
+        };
+        assert_eq!(position, round_trip(&position));
+
+        let rotation = PlayerPositionAndRotation {
+            x: 0.5,
+            y: 64.0,
+            z: -12.25,
+            yaw: 90.0,
+            pitch: -15.5,
+            on_ground: false,
+        };
+        assert_eq!(rotation, round_trip(&rotation));
+    }
+
+    #[test]
+    fn test_join_game_round_trip() {
+        let mut dimension_type = nbt::Compound::new();
+        dimension_type.insert("min_y".to_string(), Tag::Int(-64));
+        dimension_type.insert("height".to_string(), Tag::Int(384));
+        dimension_type.insert("natural".to_string(), Tag::Byte(1));
+        let dimension_type = Tag::Compound(dimension_type);
+        let join_game = JoinGame {
+            entity_id: 184,
+            hardcore: false,
+            game_mode: 1,
+            previous_game_mode: -1,
+            dimension_names: vec![
+                "minecraft:overworld".to_string(),
+                "minecraft:the_nether".to_string(),
+            ],
+            dimension_codec: Tag::Compound(nbt::Compound::new()),
+            dimension_type: dimension_type.clone(),
+            dimension: "minecraft:overworld".to_string(),
+            hashed_seed: -4_200_000_000,
+            max_players: 20,
+            view_distance: 10,
+            simulation_distance: 8,
+            reduced_debug_info: false,
+            enable_respawn_screen: true,
+            debug: false,
+            flat: true,
+        };
+        assert_eq!(join_game, round_trip(&join_game));
+        assert_eq!(
+            JoinGame {
+                simulation_distance: 10,
+                ..join_game.clone()
+            },
+            round_trip_version(&join_game, 756)
+        );
+
+        let respawn = Respawn {
+            dimension_type,
+            dimension: "minecraft:the_nether".to_string(),
+            hashed_seed: 1,
+            game_mode: 0,
+            previous_game_mode: 1,
+            debug: false,
+            flat: false,
+            copy_metadata: true,
+        };
+        assert_eq!(respawn, round_trip(&respawn));
+        // a list mixing tag types cannot be written, encoding fails instead of panicking
+        let malformed = Respawn {
+            dimension_type: Tag::List(vec![Tag::Byte(1), Tag::String("a".to_string())]),
+            ..respawn
+        };
+        assert!(malformed.to_bytes(758).is_err());
+
+        let change = ChangeGameState {
+            reason: CHANGE_GAME_MODE,
+            value: 3.0,
+        };
+        assert_eq!(change, round_trip(&change));
+
+        let difficulty = ServerDifficulty {
+            difficulty: 3,
+            locked: true,
+        };
+        assert_eq!(difficulty, round_trip(&difficulty));
+    }
+
+    #[test]
+    fn test_health_and_death_round_trip() {
+        let health = UpdateHealth {
+            health: 7.5,
+            food: 18,
+            saturation: 0.0,
+        };
+        assert_eq!(health, round_trip(&health));
+
+        let death = CombatEvent::EntityDead {
+            player_id: 184,
+            entity_id: 201,
+            message: r#"{"translate":"death.attack.mob","with":["Steve","Zombie"]}"#.to_string(),
+        };
+        assert_eq!(death, round_trip(&death));
+        assert_eq!(death, round_trip_version(&death, 754));
+        assert_eq!(
+            death.to_bytes(754).unwrap()[1..],
+            death.to_bytes(758).unwrap()[..]
+        );
+
+        let end = CombatEvent::EndCombat {
+            duration: 40,
+            entity_id: 201,
+        };
+        assert_eq!(end, round_trip_version(&end, 754));
+        assert!(CombatEvent::from_bytes(&[0x05], 754).is_err());
+
+        let respawn = ClientStatus {
+            action: PERFORM_RESPAWN,
+        };
+        assert_eq!(vec![0x00], respawn.to_bytes(758).unwrap());
+        assert_eq!(respawn, round_trip(&respawn));
+    }
+
+    #[test]
+    fn test_title_round_trip() {
+        for action in [
+            TitleAction::SetTitle(r#"{"text":"Round 2","color":"gold"}"#.to_string()),
+            TitleAction::SetSubtitle(r#"{"text":"Red team wins"}"#.to_string()),
+            TitleAction::SetActionBar(r#"{"text":"+5 coins"}"#.to_string()),
+            TitleAction::SetTimes {
+                fade_in: 10,
+                stay: 70,
+                fade_out: 20,
+            },
+            TitleAction::Hide,
+            TitleAction::Reset,
+        ] {
+            let title = Title { action };
+            assert_eq!(title, round_trip_version(&title, 754));
+        }
+        assert!(Title::from_bytes(&[0x06], 754).is_err());
+
+        let text = SetTitleText {
+            text: r#"{"text":"Round 2"}"#.to_string(),
+        };
+        assert_eq!(text, round_trip(&text));
+        let times = SetTitleTimes {
+            fade_in: 10,
+            stay: 70,
+            fade_out: 20,
+        };
+        assert_eq!(times, round_trip(&times));
+        assert_eq!(12, times.to_bytes(758).unwrap().len());
+        let clear = ClearTitles { reset: true };
+        assert_eq!(clear, round_trip(&clear));
+        assert_eq!(TitleAction::Reset, TitleAction::from(clear));
+        assert_eq!(
+            TitleAction::SetActionBar("{}".to_string()),
+            ActionBar {
+                text: "{}".to_string()
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_boss_bar_round_trip() {
+        for action in [
+            BossBarAction::Add {
+                title: r#"{"text":"Event starts in 60s"}"#.to_string(),
+                health: 1.0,
+                color: 4,
+                division: 2,
+                flags: BOSS_BAR_DARKEN_SKY | BOSS_BAR_CREATE_FOG,
+            },
+            BossBarAction::Remove,
+            BossBarAction::UpdateHealth(0.25),
+            BossBarAction::UpdateTitle(r#"{"text":"Event starts in 15s"}"#.to_string()),
+            BossBarAction::UpdateStyle {
+                color: 2,
+                division: 0,
+            },
+            BossBarAction::UpdateFlags(0),
+        ] {
+            let boss_bar = BossBar {
+                uuid: 0x069a79f444e94726a5befca90e38aaf5,
+                action,
+            };
+            assert_eq!(boss_bar, round_trip(&boss_bar));
+        }
+        let mut unknown = vec![0; 16];
+        unknown.push(0x06);
+        assert!(BossBar::from_bytes(&unknown, 758).is_err());
+    }
+
+    #[test]
+    fn test_scoreboard_round_trip() {
+        let display = DisplayScoreboard {
+            position: DISPLAY_SIDEBAR,
+            score_name: "kills".to_string(),
+        };
+        assert_eq!(display, round_trip(&display));
+
+        for action in [
+            ObjectiveAction::Create {
+                display_name: r#"{"text":"Kills","color":"red"}"#.to_string(),
+                render_type: 0,
+            },
+            ObjectiveAction::Remove,
+            ObjectiveAction::Update {
+                display_name: r#"{"text":"Hearts"}"#.to_string(),
+                render_type: 1,
+            },
+        ] {
+            let objective = ScoreboardObjective {
+                name: "kills".to_string(),
+                action,
+            };
+            assert_eq!(objective, round_trip(&objective));
+        }
+
+        for value in [Some(12), None] {
+            let score = UpdateScore {
+                entity_name: "Steve".to_string(),
+                objective_name: "kills".to_string(),
+                value,
+            };
+            assert_eq!(score, round_trip(&score));
+        }
+
+        let info = TeamInfo {
+            display_name: r#"{"text":"Red"}"#.to_string(),
+            friendly_flags: TEAM_ALLOW_FRIENDLY_FIRE,
+            name_tag_visibility: "always".to_string(),
+            collision_rule: "pushOtherTeams".to_string(),
+            color: 12,
+            prefix: r#"{"text":"[Red] "}"#.to_string(),
+            suffix: r#"{"text":""}"#.to_string(),
+        };
+        for action in [
+            TeamAction::Create(info.clone(), vec!["Steve".to_string(), "Alex".to_string()]),
+            TeamAction::Remove,
+            TeamAction::UpdateInfo(info),
+            TeamAction::AddEntities(vec!["Notch".to_string()]),
+            TeamAction::RemoveEntities(vec![]),
+        ] {
+            let teams = Teams {
+                name: "red".to_string(),
+                action,
+            };
+            assert_eq!(teams, round_trip(&teams));
+        }
+    }
+
+    #[test]
+    fn test_declare_commands_round_trip() {
+        let argument = |name: &str, parser: &str, properties, suggestions: Option<&str>| {
+            CommandNodeKind::Argument {
+                name: name.to_string(),
+                parser: parser.to_string(),
+                properties,
+                suggestions: suggestions.map(str::to_string),
+            }
+        };
+        let node = |kind, executable, children: Vec<i32>, redirect| CommandNode {
+            kind,
+            executable,
+            children,
+            redirect,
+        };
+        let commands = DeclareCommands {
+            nodes: vec![
+                node(CommandNodeKind::Root, false, vec![1, 4, 6], None),
+                node(
+                    CommandNodeKind::Literal("tp".to_string()),
+                    false,
+                    vec![2],
+                    None,
+                ),
+                node(
+                    argument(
+                        "targets",
+                        "minecraft:entity",
+                        ParserProperties::Flags(0x02),
+                        None,
+                    ),
+                    true,
+                    vec![3],
+                    None,
+                ),
+                node(
+                    argument(
+                        "distance",
+                        "brigadier:double",
+                        ParserProperties::Double {
+                            min: Some(0.0),
+                            max: None,
+                        },
+                        None,
+                    ),
+                    true,
+                    vec![],
+                    None,
+                ),
+                node(
+                    CommandNodeKind::Literal("say".to_string()),
+                    false,
+                    vec![5],
+                    None,
+                ),
+                node(
+                    argument(
+                        "message",
+                        "brigadier:string",
+                        ParserProperties::String(GREEDY_PHRASE),
+                        None,
+                    ),
+                    true,
+                    vec![],
+                    None,
+                ),
+                node(
+                    CommandNodeKind::Literal("give".to_string()),
+                    false,
+                    vec![7],
+                    None,
+                ),
+                node(
+                    argument(
+                        "item",
+                        "minecraft:item_stack",
+                        ParserProperties::None,
+                        Some("minecraft:ask_server"),
+                    ),
+                    false,
+                    vec![8],
+                    None,
+                ),
+                node(
+                    argument(
+                        "count",
+                        "brigadier:integer",
+                        ParserProperties::Integer {
+                            min: Some(1),
+                            max: Some(64),
+                        },
+                        None,
+                    ),
+                    true,
+                    vec![],
+                    None,
+                ),
+                node(
+                    CommandNodeKind::Literal("teleport".to_string()),
+                    false,
+                    vec![],
+                    Some(1),
+                ),
+            ],
+            root_index: 0,
+        };
+        assert_eq!(commands, round_trip(&commands));
+
+        let request = ServerboundTabComplete {
+            transaction_id: 3,
+            text: "/give @p minecraft:di".to_string(),
+        };
+        assert_eq!(request, round_trip(&request));
+        let response = ClientboundTabComplete {
+            transaction_id: 3,
+            start: 9,
+            length: 12,
+            matches: vec![
+                TabCompleteMatch {
+                    text: "minecraft:diamond".to_string(),
+                    tooltip: None,
+                },
+                TabCompleteMatch {
+                    text: "minecraft:diorite".to_string(),
+                    tooltip: Some(r#"{"text":"Diorite"}"#.to_string()),
+                },
+            ],
+        };
+        assert_eq!(response, round_trip(&response));
+    }
+
+    #[test]
+    fn test_settings_and_plugin_message_round_trip() {
+        let settings = ClientSettings {
+            locale: "en_us".to_string(),
+            view_distance: 8,
+            chat_mode: CHAT_COMMANDS_ONLY,
+            chat_colors: true,
+            displayed_skin_parts: ALL_SKIN_PARTS,
+            main_hand: MAIN_HAND_RIGHT,
+            text_filtering: true,
+            allow_server_listings: false,
+        };
+        assert_eq!(settings, round_trip(&settings));
+        assert_eq!(
+            ClientSettings {
+                allow_server_listings: true,
+                ..settings.clone()
+            },
+            round_trip_version(&settings, 756)
+        );
+        assert_eq!(
+            ClientSettings {
+                text_filtering: false,
+                allow_server_listings: true,
+                ..settings.clone()
+            },
+            round_trip_version(&settings, 754)
+        );
+        assert_eq!(
+            settings.to_bytes(754).unwrap().len() + 2,
+            settings.to_bytes(758).unwrap().len()
+        );
+
+        let brand = ClientboundPluginMessage {
+            channel: "minecraft:brand".to_string(),
+            data: vec![0x05, b'P', b'a', b'p', b'e', b'r'],
+        };
+        assert_eq!(brand, round_trip(&brand));
+        let empty = ServerboundPluginMessage {
+            channel: "bungeecord:main".to_string(),
+            data: vec![],
+        };
+        assert_eq!(empty, round_trip(&empty));
+    }
+}
@@ -0,0 +1,107 @@
+use std::io;
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusRequest;
+
+impl Packet for StatusRequest {
+    const KIND: PacketKind = PacketKind::StatusRequest;
+
+    fn encode(&self, _buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn decode(_reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(StatusRequest)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusResponse {
+    pub json: String,
+}
+
+impl Packet for StatusResponse {
+    const KIND: PacketKind = PacketKind::StatusResponse;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.append(&mut string_write(&self.json));
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(StatusResponse {
+            json: reader.read_string()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PingRequest {
+    pub payload: i64,
+}
+
+impl Packet for PingRequest {
+    const KIND: PacketKind = PacketKind::PingRequest;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.payload.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(PingRequest {
+            payload: reader.read_i64()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PingResponse {
+    pub payload: i64,
+}
+
+impl Packet for PingResponse {
+    const KIND: PacketKind = PacketKind::PingResponse;
+
+    fn encode(&self, buf: &mut Vec<u8>, _protocol: i32) -> io::Result<()> {
+        buf.extend_from_slice(&self.payload.to_be_bytes());
+        Ok(())
+    }
+
+    fn decode(reader: &mut PacketReader, _protocol: i32) -> io::Result<Self> {
+        Ok(PingResponse {
+            payload: reader.read_i64()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_round_trip() {
+        assert!(StatusRequest.to_bytes(758).unwrap().is_empty());
+        assert_eq!(StatusRequest, round_trip(&StatusRequest));
+
+        let response = StatusResponse {
+            json: r#"{"version":{"name":"1.18.2","protocol":758}}"#.to_string(),
+        };
+        assert_eq!(response, round_trip(&response));
+    }
+
+    #[test]
+    fn test_ping_round_trip() {
+        let request = PingRequest { payload: 92233720 };
+        assert_eq!(
+            vec![0x00, 0x00, 0x00, 0x00, 0x05, 0x7f, 0x5f, 0xf8],
+            request.to_bytes(758).unwrap()
+        );
+        assert_eq!(request, round_trip(&request));
+
+        let response = PingResponse { payload: -1 };
+        assert_eq!(response, round_trip(&response));
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use crate::chat::ChatComponent;
+use crate::packet::{PlayerEntry, PlayerInfoAction, PlayerListHeaderAndFooter};
+use crate::scoreboard::Scoreboard;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Name,
+    Ping,
+}
+
+impl SortOrder {
+    pub fn parse(name: &str) -> Option<SortOrder> {
+        match name {
+            "name" => Some(SortOrder::Name),
+            "ping" => Some(SortOrder::Ping),
+            _ => None,
+        }
+    }
+}
+
+// xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx, how uuids are shown everywhere else
+pub fn hyphenated_uuid(uuid: u128) -> String {
+    let hex = format!("{:032x}", uuid);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+// the bars the vanilla tab list shows for a ping, 0 when it is not known
+pub fn latency_bars(ping: i32) -> u8 {
+    match ping {
+        i32::MIN..=-1 => 0,
+        0..=149 => 5,
+        150..=299 => 4,
+        300..=599 => 3,
+        600..=999 => 2,
+        _ => 1,
+    }
+}
+
+const SPECTATOR: i32 = 3;
+
+// the name the vanilla tab list shows: the display name, or the name formatted by the team
+pub fn tab_name(entry: &PlayerEntry, scoreboard: &Scoreboard) -> ChatComponent {
+    let mut name = match &entry.display_name {
+        Some(json) => ChatComponent::from_json_lossy(json),
+        None => scoreboard.format_name(&entry.name),
+    };
+    if entry.game_mode == SPECTATOR {
+        name.style.italic = Some(true);
+    }
+    name
+}
+
+// The tab list: everyone the server has announced with Player Info, by uuid, and the text
+// shown above and below them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerList {
+    entries: HashMap<u128, PlayerEntry>,
+    header: Option<ChatComponent>,
+    footer: Option<ChatComponent>,
+}
+
+impl PlayerList {
+    pub fn new() -> PlayerList {
+        PlayerList::default()
+    }
+
+    // updates for players that were never added are ignored, like the vanilla client does
+    pub fn apply(&mut self, action: PlayerInfoAction) {
+        match action {
+            PlayerInfoAction::AddPlayer(players) => {
+                for player in players {
+                    self.entries.insert(player.uuid, player);
+                }
+            }
+            PlayerInfoAction::UpdateGameMode(players) => {
+                for (uuid, game_mode) in players {
+                    if let Some(entry) = self.entries.get_mut(&uuid) {
+                        entry.game_mode = game_mode;
+                    }
+                }
+            }
+            PlayerInfoAction::UpdateLatency(players) => {
+                for (uuid, ping) in players {
+                    if let Some(entry) = self.entries.get_mut(&uuid) {
+                        entry.ping = ping;
+                    }
+                }
+            }
+            PlayerInfoAction::UpdateDisplayName(players) => {
+                for (uuid, display_name) in players {
+                    if let Some(entry) = self.entries.get_mut(&uuid) {
+                        entry.display_name = display_name;
+                    }
+                }
+            }
+            PlayerInfoAction::RemovePlayer(players) => {
+                for uuid in players {
+                    self.entries.remove(&uuid);
+                }
+            }
+        }
+    }
+
+    pub fn set_header_and_footer(&mut self, packet: &PlayerListHeaderAndFooter) {
+        let parse = |json: &str| {
+            Some(ChatComponent::from_json_lossy(json)).filter(|component| !component.is_empty())
+        };
+        self.header = parse(&packet.header);
+        self.footer = parse(&packet.footer);
+    }
+
+    pub fn header(&self) -> Option<&ChatComponent> {
+        self.header.as_ref()
+    }
+
+    pub fn footer(&self) -> Option<&ChatComponent> {
+        self.footer.as_ref()
+    }
+
+    pub fn get(&self, uuid: u128) -> Option<&PlayerEntry> {
+        self.entries.get(&uuid)
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&PlayerEntry> {
+        self.entries.values().find(|entry| entry.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PlayerEntry> {
+        self.entries.values()
+    }
+
+    // names compare case insensitively, ties in ping are sorted by name
+    pub fn sorted(&self, order: SortOrder) -> Vec<&PlayerEntry> {
+        let mut entries: Vec<&PlayerEntry> = self.entries.values().collect();
+        entries.sort_by_cached_key(|entry| (entry.name.to_lowercase(), entry.uuid));
+        if order == SortOrder::Ping {
+            entries.sort_by_key(|entry| entry.ping);
+        }
+        entries
+    }
+
+    // the order of the vanilla tab list: spectators last, then by team name, then by name
+    pub fn tab_order(&self, scoreboard: &Scoreboard) -> Vec<&PlayerEntry> {
+        let mut entries = self.sorted(SortOrder::Name);
+        entries.sort_by_cached_key(|entry| {
+            let team = scoreboard
+                .team_of(&entry.name)
+                .map(|team| team.name.clone());
+            (entry.game_mode == SPECTATOR, team)
+        });
+        entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::{PlayerProperty, TeamAction, TeamInfo, Teams};
+    use crate::translation::Translations;
+
+    fn entry(uuid: u128, name: &str) -> PlayerEntry {
+        PlayerEntry {
+            uuid,
+            name: name.to_string(),
+            properties: vec![PlayerProperty {
+                name: "textures".to_string(),
+                value: "e30=".to_string(),
+                signature: None,
+            }],
+            game_mode: 0,
+            ping: 0,
+            display_name: None,
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut players = PlayerList::new();
+        players.apply(PlayerInfoAction::AddPlayer(vec![
+            entry(1, "Steve"),
+            entry(2, "Alex"),
+        ]));
+        players.apply(PlayerInfoAction::UpdateGameMode(vec![(1, 3), (9, 1)]));
+        players.apply(PlayerInfoAction::UpdateLatency(vec![(2, 120)]));
+        players.apply(PlayerInfoAction::UpdateDisplayName(vec![(
+            2,
+            Some(r#"{"text":"[Admin] Alex"}"#.to_string()),
+        )]));
+
+        assert_eq!(2, players.len());
+        assert_eq!(3, players.get(1).unwrap().game_mode);
+        assert_eq!(None, players.get(9));
+        let alex = players.find_by_name("Alex").unwrap();
+        assert_eq!(120, alex.ping);
+        assert_eq!(
+            Some(r#"{"text":"[Admin] Alex"}"#),
+            alex.display_name.as_deref()
+        );
+        assert_eq!("textures", alex.properties[0].name);
+
+        players.apply(PlayerInfoAction::UpdateDisplayName(vec![(2, None)]));
+        players.apply(PlayerInfoAction::RemovePlayer(vec![1]));
+        assert_eq!(1, players.len());
+        assert_eq!(None, players.get(2).unwrap().display_name);
+    }
+
+    #[test]
+    fn test_header_and_footer() {
+        let mut players = PlayerList::new();
+        players.set_header_and_footer(&PlayerListHeaderAndFooter {
+            header: r#"{"text":"My Server","bold":true}"#.to_string(),
+            footer: r#"{"text":""}"#.to_string(),
+        });
+        assert_eq!(
+            Some(&ChatComponent::from_json_lossy(
+                r#"{"text":"My Server","bold":true}"#
+            )),
+            players.header()
+        );
+        assert_eq!(None, players.footer());
+    }
+
+    #[test]
+    fn test_formatting() {
+        assert_eq!(
+            "069a79f4-44e9-4726-a5be-fca90e38aaf5",
+            hyphenated_uuid(0x069a79f444e94726a5befca90e38aaf5)
+        );
+        assert_eq!("00000000-0000-0000-0000-000000000001", hyphenated_uuid(1));
+        assert_eq!(
+            vec![0, 5, 5, 4, 3, 2, 1],
+            [-1, 0, 149, 150, 599, 999, 1000].map(latency_bars)
+        );
+    }
+
+    #[test]
+    fn test_sorted() {
+        let mut players = PlayerList::new();
+        let mut steve = entry(1, "Steve");
+        steve.ping = 20;
+        let mut alex = entry(2, "alex");
+        alex.ping = 300;
+        let mut notch = entry(3, "Notch");
+        notch.ping = 20;
+        players.apply(PlayerInfoAction::AddPlayer(vec![steve, alex, notch]));
+
+        let names = |order| -> Vec<String> {
+            players
+                .sorted(order)
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect()
+        };
+        assert_eq!(vec!["alex", "Notch", "Steve"], names(SortOrder::Name));
+        assert_eq!(vec!["Notch", "Steve", "alex"], names(SortOrder::Ping));
+
+        players.apply(PlayerInfoAction::UpdateGameMode(vec![(2, 3)]));
+        let mut scoreboard = Scoreboard::new();
+        scoreboard.team(Teams {
+            name: "a_team".to_string(),
+            action: TeamAction::Create(
+                TeamInfo {
+                    display_name: r#"{"text":"A"}"#.to_string(),
+                    friendly_flags: 0,
+                    name_tag_visibility: "always".to_string(),
+                    collision_rule: "always".to_string(),
+                    color: 21,
+                    prefix: r#"{"text":"[A] "}"#.to_string(),
+                    suffix: r#"{"text":""}"#.to_string(),
+                },
+                vec!["Notch".to_string()],
+            ),
+        });
+        let tab: Vec<&str> = players
+            .tab_order(&scoreboard)
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        // like the vanilla client, players without a team come first
+        assert_eq!(vec!["Steve", "Notch", "alex"], tab);
+
+        let translations = Translations::default();
+        let name = |uuid| tab_name(players.get(uuid).unwrap(), &scoreboard);
+        assert_eq!("[A] Notch", name(3).to_plain(&translations));
+        assert_eq!(Some(true), name(2).style.italic);
+        assert_eq!(Some(SortOrder::Ping), SortOrder::parse("ping"));
+        assert_eq!(None, SortOrder::parse("uuid"));
+    }
+}
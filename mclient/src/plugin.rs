@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::io;
+
+use crate::packet::{
+    ClientboundPluginMessage, LoginPluginRequest, LoginPluginResponse, PacketReader,
+    ServerboundPluginMessage,
+};
+use crate::varint_type::string_write;
+
+// the server software on the way in, the client software on the way out
+pub const BRAND: &str = "minecraft:brand";
+
+// Handles the plugin messages of one channel, as registered with PluginChannels
+pub trait PluginMessageHandler {
+    // the plugin messages to send back, if any
+    fn handle(&mut self, data: &[u8]) -> io::Result<Vec<ServerboundPluginMessage>>;
+}
+
+// The handlers of the channels the client understands, messages on other channels are
+// ignored like the vanilla client does
+#[derive(Default)]
+pub struct PluginChannels {
+    handlers: HashMap<String, Box<dyn PluginMessageHandler>>,
+}
+
+impl PluginChannels {
+    pub fn new() -> PluginChannels {
+        PluginChannels::default()
+    }
+
+    // replaces the handler the channel had
+    pub fn register(&mut self, channel: &str, handler: Box<dyn PluginMessageHandler>) {
+        self.handlers.insert(channel.to_string(), handler);
+    }
+
+    pub fn is_registered(&self, channel: &str) -> bool {
+        self.handlers.contains_key(channel)
+    }
+
+    // None when no handler is registered for the channel
+    pub fn dispatch(
+        &mut self,
+        message: &ClientboundPluginMessage,
+    ) -> Option<io::Result<Vec<ServerboundPluginMessage>>> {
+        let handler = self.handlers.get_mut(&message.channel)?;
+        Some(handler.handle(&message.data))
+    }
+}
+
+// Answers the Login Plugin Requests of one channel, as registered with LoginPluginChannels.
+// Proxies like Velocity use them to forward player information before Login Success
+pub trait LoginPluginHandler {
+    // the data of the response, None when the request is not understood
+    fn handle(&mut self, data: &[u8]) -> Option<Vec<u8>>;
+}
+
+// The handlers of the login channels the client understands, requests on other channels
+// are answered with "not understood" like the vanilla client does
+#[derive(Default)]
+pub struct LoginPluginChannels {
+    handlers: HashMap<String, Box<dyn LoginPluginHandler>>,
+}
+
+impl LoginPluginChannels {
+    pub fn new() -> LoginPluginChannels {
+        LoginPluginChannels::default()
+    }
+
+    // replaces the handler the channel had
+    pub fn register(&mut self, channel: &str, handler: Box<dyn LoginPluginHandler>) {
+        self.handlers.insert(channel.to_string(), handler);
+    }
+
+    pub fn is_registered(&self, channel: &str) -> bool {
+        self.handlers.contains_key(channel)
+    }
+
+    // every request needs a response with its message id, or the server waits for it
+    pub fn respond(&mut self, request: &LoginPluginRequest) -> LoginPluginResponse {
+        let data = self
+            .handlers
+            .get_mut(&request.channel)
+            .and_then(|handler| handler.handle(&request.data));
+
+        LoginPluginResponse {
+            message_id: request.message_id,
+            data,
+        }
+    }
+}
+
+// the brand is a single string, what the vanilla client sends is "vanilla"
+pub fn brand_message(brand: &str) -> ServerboundPluginMessage {
+    ServerboundPluginMessage {
+        channel: BRAND.to_string(),
+        data: string_write(brand),
+    }
+}
+
+pub fn read_brand(data: &[u8]) -> io::Result<String> {
+    PacketReader::new(data).read_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // answers every message with the same data on another channel
+    struct Echo;
+
+    impl PluginMessageHandler for Echo {
+        fn handle(&mut self, data: &[u8]) -> io::Result<Vec<ServerboundPluginMessage>> {
+            Ok(vec![ServerboundPluginMessage {
+                channel: "test:echo_reply".to_string(),
+                data: data.to_vec(),
+            }])
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let mut channels = PluginChannels::new();
+        channels.register("test:echo", Box::new(Echo));
+        assert!(channels.is_registered("test:echo"));
+
+        let message = |channel: &str| ClientboundPluginMessage {
+            channel: channel.to_string(),
+            data: vec![1, 2, 3],
+        };
+        let replies = channels.dispatch(&message("test:echo")).unwrap().unwrap();
+        assert_eq!("test:echo_reply", replies[0].channel);
+        assert_eq!(vec![1, 2, 3], replies[0].data);
+        assert!(channels.dispatch(&message("test:other")).is_none());
+    }
+
+    // understands requests with data only
+    struct NonEmpty;
+
+    impl LoginPluginHandler for NonEmpty {
+        fn handle(&mut self, data: &[u8]) -> Option<Vec<u8>> {
+            (!data.is_empty()).then(|| data.iter().rev().copied().collect())
+        }
+    }
+
+    #[test]
+    fn test_login_respond() {
+        let mut channels = LoginPluginChannels::new();
+        channels.register("test:reverse", Box::new(NonEmpty));
+        assert!(channels.is_registered("test:reverse"));
+
+        let request = |message_id: i32, channel: &str, data: Vec<u8>| LoginPluginRequest {
+            message_id,
+            channel: channel.to_string(),
+            data,
+        };
+        assert_eq!(
+            LoginPluginResponse {
+                message_id: 1,
+                data: Some(vec![3, 2, 1]),
+            },
+            channels.respond(&request(1, "test:reverse", vec![1, 2, 3]))
+        );
+        assert_eq!(
+            LoginPluginResponse {
+                message_id: 2,
+                data: None,
+            },
+            channels.respond(&request(2, "test:reverse", vec![]))
+        );
+        assert_eq!(
+            LoginPluginResponse {
+                message_id: 3,
+                data: None,
+            },
+            channels.respond(&request(3, "velocity:player_info", vec![1]))
+        );
+    }
+
+    #[test]
+    fn test_brand() {
+        let message = brand_message("vanilla");
+        assert_eq!(BRAND, message.channel);
+        assert_eq!("vanilla", read_brand(&message.data).unwrap());
+        assert!(read_brand(&[0x05, b'P']).is_err());
+    }
+}
@@ -0,0 +1,443 @@
+use std::io::{self, Read};
+
+use crate::nbt::{self, Tag};
+use crate::varint_type::*;
+
+// the protocol limits packets to 2^21 - 1 bytes (a 3 byte varint length)
+pub const MAX_FRAME_LENGTH: usize = 2097151;
+
+fn eof(needed: usize, left: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("Packet ended early: needed {} bytes, {} left", needed, left),
+    )
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Cursor over the bytes of one packet. Reads never copy unless they have to produce an
+// owned value, and running out of bytes is an error instead of a panic
+pub struct PacketReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PacketReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        PacketReader { data, position: 0 }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    // all the bytes that were not read yet
+    pub fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.position..];
+        self.position = self.data.len();
+        rest
+    }
+
+    pub fn read_bytes(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        if length > self.remaining() {
+            return Err(eof(length, self.remaining()));
+        }
+
+        let bytes = &self.data[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut res = [0u8; N];
+        res.copy_from_slice(self.read_bytes(N)?);
+        Ok(res)
+    }
+
+    pub fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_i8(&mut self) -> io::Result<i8> {
+        Ok(self.read_u8()? as i8)
+    }
+
+    pub fn read_bool(&mut self) -> io::Result<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(invalid(format!("Invalid boolean {:#04x}", value))),
+        }
+    }
+
+    pub fn read_u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_i16(&mut self) -> io::Result<i16> {
+        Ok(i16::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_i32(&mut self) -> io::Result<i32> {
+        Ok(i32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_i64(&mut self) -> io::Result<i64> {
+        Ok(i64::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_f64(&mut self) -> io::Result<f64> {
+        Ok(f64::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_uuid(&mut self) -> io::Result<u128> {
+        Ok(u128::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_varint(&mut self) -> io::Result<i32> {
+        varint_read(self)
+    }
+
+    pub fn read_varlong(&mut self) -> io::Result<i64> {
+        varlong_read(self)
+    }
+
+    // block position packed in a long: x (26 bits) | z (26 bits) | y (12 bits)
+    pub fn read_position(&mut self) -> io::Result<Position> {
+        let value = self.read_i64()?;
+        Ok(Position {
+            x: (value >> 38) as i32,
+            y: (value << 52 >> 52) as i32,
+            z: (value << 26 >> 38) as i32,
+        })
+    }
+
+    // rotation in steps of 1/256 of a full turn, in degrees
+    pub fn read_angle(&mut self) -> io::Result<f32> {
+        Ok(self.read_u8()? as f32 * 360.0 / 256.0)
+    }
+
+    // fixed-point numbers with 5 fraction bits, used for entity positions before 1.9
+    pub fn read_fixed_point(&mut self) -> io::Result<f64> {
+        Ok(self.read_i32()? as f64 / 32.0)
+    }
+
+    pub fn read_fixed_point_byte(&mut self) -> io::Result<f64> {
+        Ok(self.read_i8()? as f64 / 32.0)
+    }
+
+    // varint number of longs, then the longs
+    pub fn read_bitset(&mut self) -> io::Result<BitSet> {
+        Ok(BitSet {
+            words: self.read_prefixed_array(self.remaining() / 8, |reader| reader.read_i64())?,
+        })
+    }
+
+    // a bitset whose size is known, sent as ceil(bits / 8) bytes without a length
+    pub fn read_fixed_bitset(&mut self, bits: usize) -> io::Result<BitSet> {
+        let bytes = self.read_bytes(bits.div_ceil(8))?;
+
+        let mut bitset = BitSet::default();
+        for index in 0..bits {
+            if bytes[index / 8] & (1 << (index % 8)) != 0 {
+                bitset.set(index, true);
+            }
+        }
+        Ok(bitset)
+    }
+
+    // varint length prefixed array of at most max_length elements, each read by the given
+    // function
+    pub fn read_prefixed_array<T>(
+        &mut self,
+        max_length: usize,
+        mut element_read: impl FnMut(&mut Self) -> io::Result<T>,
+    ) -> io::Result<Vec<T>> {
+        let length = self.read_varint()?;
+        if length < 0 || length as usize > max_length {
+            return Err(invalid(format!(
+                "Length {} out of range (max {})",
+                length, max_length
+            )));
+        }
+
+        let mut res = Vec::with_capacity((length as usize).min(self.remaining()));
+        for _ in 0..length {
+            res.push(element_read(self)?);
+        }
+        Ok(res)
+    }
+
+    // boolean prefix, then the value if it is true
+    pub fn read_option<T>(
+        &mut self,
+        element_read: impl FnOnce(&mut Self) -> io::Result<T>,
+    ) -> io::Result<Option<T>> {
+        if self.read_bool()? {
+            Ok(Some(element_read(self)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // a varint length that must fit in what is left of the packet
+    fn read_length(&mut self) -> io::Result<usize> {
+        let length = self.read_varint()?;
+        if length < 0 {
+            return Err(invalid(format!("Negative length {}", length)));
+        }
+        if length as usize > self.remaining() {
+            return Err(eof(length as usize, self.remaining()));
+        }
+        Ok(length as usize)
+    }
+
+    pub fn read_byte_array(&mut self) -> io::Result<&'a [u8]> {
+        let length = self.read_length()?;
+        self.read_bytes(length)
+    }
+
+    pub fn read_str(&mut self) -> io::Result<&'a str> {
+        std::str::from_utf8(self.read_byte_array()?).map_err(|error| invalid(error.to_string()))
+    }
+
+    pub fn read_string(&mut self) -> io::Result<String> {
+        Ok(self.read_str()?.to_string())
+    }
+
+    pub fn read_optional_string(&mut self) -> io::Result<Option<String>> {
+        if self.read_bool()? {
+            Ok(Some(self.read_string()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // a named NBT root, as packets carry it before 1.20.2. None for a lone TAG_End
+    pub fn read_nbt(&mut self) -> io::Result<Option<Tag>> {
+        Ok(nbt::read_named(self)?.map(|(_, tag)| tag))
+    }
+}
+
+impl Read for PacketReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = buf.len().min(self.remaining());
+        buf[..length].copy_from_slice(&self.data[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+// Reads one length-prefixed frame at a time from a stream. Bytes are buffered until a whole
+// frame arrived, so a read that times out (a socket with a short read timeout) can simply
+// be tried again without losing the part of the frame that was already read
+pub struct FrameReader<R: Read> {
+    inner: R,
+    buffer: Vec<u8>,  // received bytes, the next frame starts at 0
+    frame_end: usize, // length of the frame returned last, dropped from buffer on the next read
+}
+
+// bytes asked from the stream at once
+const READ_CHUNK: usize = 4096;
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(inner: R) -> Self {
+        FrameReader {
+            inner,
+            buffer: vec![],
+            frame_end: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    // bytes received but not returned in a frame yet
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer[self.frame_end..]
+    }
+
+    pub fn read_frame(&mut self) -> io::Result<&[u8]> {
+        self.buffer.drain(..self.frame_end);
+        self.frame_end = 0;
+
+        loop {
+            if let Some((header_length, length)) = self.frame_header()? {
+                if self.buffer.len() >= header_length + length {
+                    self.frame_end = header_length + length;
+                    return Ok(&self.buffer[header_length..self.frame_end]);
+                }
+            }
+
+            let filled = self.buffer.len();
+            self.buffer.resize(filled + READ_CHUNK, 0);
+            let read = self.inner.read(&mut self.buffer[filled..]);
+            // on an error nothing was read, the buffered bytes stay for the next try
+            self.buffer
+                .truncate(filled + read.as_ref().map_or(0, |read| *read));
+            if read? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+    }
+
+    // the size of the length varint and the length, None while the varint is incomplete
+    fn frame_header(&self) -> io::Result<Option<(usize, usize)>> {
+        let Some(header_length) = self.buffer.iter().take(3).position(|byte| byte & 0x80 == 0)
+        else {
+            return match self.buffer.len() {
+                length if length >= 3 => Err(invalid("Packet length too big".to_string())),
+                _ => Ok(None),
+            };
+        };
+
+        let length = varint_read(&mut &self.buffer[..=header_length])?;
+        if length <= 0 || length as usize > MAX_FRAME_LENGTH {
+            return Err(invalid(format!("Invalid packet length {}", length)));
+        }
+        Ok(Some((header_length + 1, length as usize)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitives() {
+        let mut data: Vec<u8> = vec![0x01, 0xff, 0x12, 0x34];
+        data.extend_from_slice(&(-5i32).to_be_bytes());
+        data.extend_from_slice(&i64::MIN.to_be_bytes());
+        data.extend_from_slice(&1.5f32.to_be_bytes());
+        data.extend_from_slice(&(-0.25f64).to_be_bytes());
+        data.extend_from_slice(&7u128.to_be_bytes());
+        data.extend(varint_write(300));
+        data.extend(varlong_write(i64::MAX));
+        data.extend(string_write("héllo"));
+        data.extend(byte_array_write(&[1, 2, 3]));
+        data.extend([0x00]);
+        data.extend(position_write(Position { x: 1, y: -2, z: 3 }));
+        data.extend(angle_write(180.0));
+
+        let mut reader = PacketReader::new(&data);
+        assert!(reader.read_bool().unwrap());
+        assert_eq!(-1, reader.read_i8().unwrap());
+        assert_eq!(0x1234, reader.read_u16().unwrap());
+        assert_eq!(-5, reader.read_i32().unwrap());
+        assert_eq!(i64::MIN, reader.read_i64().unwrap());
+        assert_eq!(1.5, reader.read_f32().unwrap());
+        assert_eq!(-0.25, reader.read_f64().unwrap());
+        assert_eq!(7, reader.read_uuid().unwrap());
+        assert_eq!(300, reader.read_varint().unwrap());
+        assert_eq!(i64::MAX, reader.read_varlong().unwrap());
+        assert_eq!("héllo", reader.read_str().unwrap());
+        assert_eq!(&[1, 2, 3], reader.read_byte_array().unwrap());
+        assert_eq!(None, reader.read_optional_string().unwrap());
+        assert_eq!(
+            Position { x: 1, y: -2, z: 3 },
+            reader.read_position().unwrap()
+        );
+        assert_eq!(180.0, reader.read_angle().unwrap());
+        assert!(reader.is_empty());
+        assert!(reader.read_u8().is_err());
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut reader = PacketReader::new(&[0x05, b'a', b'b']);
+        assert!(reader.read_string().is_err());
+
+        let mut reader = PacketReader::new(&[0x02]);
+        assert!(reader.read_bool().is_err());
+
+        // two longs announced, one sent
+        let mut reader = PacketReader::new(&[0x02, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+        assert!(reader.read_bitset().is_err());
+
+        let mut reader = PacketReader::new(&[0x00, 0x01]);
+        assert_eq!(0, reader.read_u8().unwrap());
+        assert!(reader.read_i16().is_err());
+        assert_eq!(&[0x01], reader.rest());
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut stream: Vec<u8> = vec![];
+        stream.extend(byte_array_write(&[0x21, 0x01]));
+        stream.extend(byte_array_write(&vec![0xaa; 300]));
+        stream.extend([0x03, 0x0f]); // truncated frame
+
+        let mut frames = FrameReader::new(stream.as_slice());
+        assert_eq!(&[0x21, 0x01], frames.read_frame().unwrap());
+        assert_eq!(300, frames.read_frame().unwrap().len());
+        assert!(frames.read_frame().is_err());
+
+        let mut frames = FrameReader::new([0x00].as_slice());
+        assert!(frames.read_frame().is_err());
+        let mut frames = FrameReader::new([0xff, 0xff, 0xff, 0x7f].as_slice());
+        assert!(frames.read_frame().is_err());
+    }
+
+    // hands out its bytes in the given pieces, timing out between them like a socket
+    struct Trickle(Vec<Vec<u8>>, bool);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.1 = !self.1;
+            if self.1 || self.0.is_empty() {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let piece = self.0.remove(0);
+            buf[..piece.len()].copy_from_slice(&piece);
+            Ok(piece.len())
+        }
+    }
+
+    #[test]
+    fn test_resume_after_timeout() {
+        let mut frames = FrameReader::new(Trickle(
+            vec![
+                vec![0x82],
+                vec![0x01],
+                vec![0xaa; 100],
+                vec![0xbb; 30],
+                vec![0x01, 0x07],
+            ],
+            false,
+        ));
+        let mut read = || loop {
+            match frames.read_frame() {
+                Ok(frame) => return frame.to_vec(),
+                Err(error) => assert_eq!(io::ErrorKind::WouldBlock, error.kind()),
+            }
+        };
+
+        let frame = read();
+        assert_eq!(130, frame.len());
+        assert_eq!((0xaa, 0xbb), (frame[0], frame[129]));
+        assert_eq!(vec![0x07], read());
+    }
+}
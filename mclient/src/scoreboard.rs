@@ -0,0 +1,405 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::chat::{ChatComponent, Color, Content};
+use crate::packet::{
+    DisplayScoreboard, ObjectiveAction, ScoreboardObjective, TeamAction, TeamInfo, Teams,
+    UpdateScore, DISPLAY_SIDEBAR,
+};
+
+// the vanilla sidebar shows the 15 highest scores
+pub const SIDEBAR_LINES: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Objective {
+    pub name: String,
+    pub display_name: ChatComponent,
+    pub render_type: i32, // 0: integer, 1: hearts
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Team {
+    pub name: String,
+    pub display_name: ChatComponent,
+    pub prefix: ChatComponent,
+    pub suffix: ChatComponent,
+    pub color: Option<Color>,
+    pub friendly_flags: u8,
+    pub name_tag_visibility: String,
+    pub collision_rule: String,
+    pub members: BTreeSet<String>,
+}
+
+impl Team {
+    fn new(name: &str, info: TeamInfo) -> Team {
+        let mut team = Team {
+            name: name.to_string(),
+            display_name: ChatComponent::text(name),
+            prefix: ChatComponent::text(""),
+            suffix: ChatComponent::text(""),
+            color: None,
+            friendly_flags: 0,
+            name_tag_visibility: String::new(),
+            collision_rule: String::new(),
+            members: BTreeSet::new(),
+        };
+        team.update(info);
+        team
+    }
+
+    fn update(&mut self, info: TeamInfo) {
+        self.display_name = ChatComponent::from_json_lossy(&info.display_name);
+        self.prefix = ChatComponent::from_json_lossy(&info.prefix);
+        self.suffix = ChatComponent::from_json_lossy(&info.suffix);
+        self.color = Color::from_index(info.color);
+        self.friendly_flags = info.friendly_flags;
+        self.name_tag_visibility = info.name_tag_visibility;
+        self.collision_rule = info.collision_rule;
+    }
+
+    // prefix, name and suffix in the team color: how the vanilla client shows team members.
+    // Parts with a color of their own keep it
+    pub fn format_name(&self, name: ChatComponent) -> ChatComponent {
+        let mut formatted = ChatComponent::text("");
+        formatted.style.color = self.color;
+        formatted.extra = vec![self.prefix.clone(), name, self.suffix.clone()];
+        formatted
+    }
+}
+
+// The objectives, scores and teams the server has sent, and which objective is shown where
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scoreboard {
+    objectives: HashMap<String, Objective>,
+    // objective -> entity -> score
+    scores: HashMap<String, HashMap<String, i32>>,
+    displayed: HashMap<i8, String>,
+    teams: HashMap<String, Team>,
+}
+
+impl Scoreboard {
+    pub fn new() -> Scoreboard {
+        Scoreboard::default()
+    }
+
+    pub fn display(&mut self, packet: DisplayScoreboard) {
+        if packet.score_name.is_empty() {
+            self.displayed.remove(&packet.position);
+        } else {
+            self.displayed.insert(packet.position, packet.score_name);
+        }
+    }
+
+    pub fn objective(&mut self, packet: ScoreboardObjective) {
+        match packet.action {
+            ObjectiveAction::Create {
+                display_name,
+                render_type,
+            }
+            | ObjectiveAction::Update {
+                display_name,
+                render_type,
+            } => {
+                self.objectives.insert(
+                    packet.name.clone(),
+                    Objective {
+                        name: packet.name,
+                        display_name: ChatComponent::from_json_lossy(&display_name),
+                        render_type,
+                    },
+                );
+            }
+            ObjectiveAction::Remove => {
+                self.objectives.remove(&packet.name);
+                self.scores.remove(&packet.name);
+                self.displayed.retain(|_, name| *name != packet.name);
+            }
+        }
+    }
+
+    pub fn score(&mut self, packet: UpdateScore) {
+        match packet.value {
+            Some(value) => {
+                self.scores
+                    .entry(packet.objective_name)
+                    .or_default()
+                    .insert(packet.entity_name, value);
+            }
+            None if packet.objective_name.is_empty() => {
+                for scores in self.scores.values_mut() {
+                    scores.remove(&packet.entity_name);
+                }
+            }
+            None => {
+                if let Some(scores) = self.scores.get_mut(&packet.objective_name) {
+                    scores.remove(&packet.entity_name);
+                }
+            }
+        }
+    }
+
+    pub fn team(&mut self, packet: Teams) {
+        match packet.action {
+            TeamAction::Create(info, members) => {
+                let team = Team::new(&packet.name, info);
+                self.teams.insert(packet.name.clone(), team);
+                self.add_members(&packet.name, members);
+            }
+            TeamAction::Remove => {
+                self.teams.remove(&packet.name);
+            }
+            TeamAction::UpdateInfo(info) => {
+                if let Some(team) = self.teams.get_mut(&packet.name) {
+                    team.update(info);
+                }
+            }
+            TeamAction::AddEntities(members) => {
+                self.add_members(&packet.name, members);
+            }
+            TeamAction::RemoveEntities(members) => {
+                if let Some(team) = self.teams.get_mut(&packet.name) {
+                    for member in members {
+                        team.members.remove(&member);
+                    }
+                }
+            }
+        }
+    }
+
+    // an entity is in one team at most, joining one leaves the team it was in like in vanilla
+    fn add_members(&mut self, team_name: &str, members: Vec<String>) {
+        if !self.teams.contains_key(team_name) {
+            return;
+        }
+        for team in self.teams.values_mut() {
+            if team.name != team_name {
+                for member in &members {
+                    team.members.remove(member);
+                }
+            }
+        }
+        if let Some(team) = self.teams.get_mut(team_name) {
+            team.members.extend(members);
+        }
+    }
+
+    pub fn get_objective(&self, name: &str) -> Option<&Objective> {
+        self.objectives.get(name)
+    }
+
+    pub fn get_score(&self, objective: &str, entity: &str) -> Option<i32> {
+        self.scores.get(objective)?.get(entity).copied()
+    }
+
+    pub fn get_team(&self, name: &str) -> Option<&Team> {
+        self.teams.get(name)
+    }
+
+    pub fn team_of(&self, entity: &str) -> Option<&Team> {
+        self.teams
+            .values()
+            .find(|team| team.members.contains(entity))
+    }
+
+    // the name with the prefix, color and suffix of the team of the entity, if it has one
+    pub fn format_name(&self, entity: &str) -> ChatComponent {
+        let name = ChatComponent::text(entity);
+        match self.team_of(entity) {
+            Some(team) => team.format_name(name),
+            None => name,
+        }
+    }
+
+    // the objective in the sidebar of `player`: the one for the color of its team if there
+    // is one, else the common one
+    pub fn sidebar_objective(&self, player: &str) -> Option<&Objective> {
+        let team_slot = self
+            .team_of(player)
+            .and_then(|team| team.color)
+            .and_then(|color| (0..16).find(|index| Color::from_index(*index) == Some(color)))
+            .and_then(|index| self.displayed.get(&(3 + index as i8)));
+
+        team_slot
+            .or_else(|| self.displayed.get(&DISPLAY_SIDEBAR))
+            .and_then(|name| self.objectives.get(name))
+    }
+
+    // the lines of the sidebar, highest score first and names in order for ties; entities
+    // starting with # are hidden like the vanilla client does
+    pub fn sidebar_lines(&self, objective: &Objective) -> Vec<(&str, i32)> {
+        let mut lines: Vec<(&str, i32)> = self
+            .scores
+            .get(&objective.name)
+            .map(|scores| {
+                scores
+                    .iter()
+                    .filter(|(entity, _)| !entity.starts_with('#'))
+                    .map(|(entity, score)| (entity.as_str(), *score))
+                    .collect()
+            })
+            .unwrap_or_default();
+        lines.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        lines.truncate(SIDEBAR_LINES);
+        lines
+    }
+
+    // Servers that follow vanilla put the team prefix in the sender of chat messages already,
+    // senders sent as their bare name get it here
+    pub fn decorate_chat(&self, component: &mut ChatComponent) {
+        let Content::Translate { key, with } = &mut component.content else {
+            return;
+        };
+        if !key.starts_with("chat.type.") {
+            return;
+        }
+        let Some(sender) = with.first_mut() else {
+            return;
+        };
+        let team = match &sender.content {
+            Content::Text(name) if sender.extra.is_empty() => self.team_of(name),
+            _ => None,
+        };
+        if let Some(team) = team {
+            *sender = team.format_name(sender.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translation::Translations;
+
+    fn red_team(members: &[&str]) -> Teams {
+        Teams {
+            name: "red".to_string(),
+            action: TeamAction::Create(
+                TeamInfo {
+                    display_name: r#"{"text":"Red"}"#.to_string(),
+                    friendly_flags: 0,
+                    name_tag_visibility: "always".to_string(),
+                    collision_rule: "always".to_string(),
+                    color: 12,
+                    prefix: r#"{"text":"[Red] "}"#.to_string(),
+                    suffix: r#"{"text":""}"#.to_string(),
+                },
+                members.iter().map(|member| member.to_string()).collect(),
+            ),
+        }
+    }
+
+    fn score(entity: &str, value: Option<i32>) -> UpdateScore {
+        UpdateScore {
+            entity_name: entity.to_string(),
+            objective_name: "kills".to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_sidebar() {
+        let mut scoreboard = Scoreboard::new();
+        scoreboard.objective(ScoreboardObjective {
+            name: "kills".to_string(),
+            action: ObjectiveAction::Create {
+                display_name: r#"{"text":"Kills"}"#.to_string(),
+                render_type: 0,
+            },
+        });
+        scoreboard.display(DisplayScoreboard {
+            position: DISPLAY_SIDEBAR,
+            score_name: "kills".to_string(),
+        });
+        scoreboard.score(score("Steve", Some(3)));
+        scoreboard.score(score("Alex", Some(7)));
+        scoreboard.score(score("Notch", Some(3)));
+        scoreboard.score(score("#hidden", Some(99)));
+        scoreboard.score(score("Herobrine", Some(1)));
+        scoreboard.score(score("Herobrine", None));
+
+        let objective = scoreboard.sidebar_objective("Steve").unwrap();
+        assert_eq!("kills", objective.name);
+        assert_eq!(
+            vec![("Alex", 7), ("Notch", 3), ("Steve", 3)],
+            scoreboard.sidebar_lines(objective)
+        );
+
+        // members of a red team see the sidebar for red if there is one
+        scoreboard.team(red_team(&["Steve"]));
+        scoreboard.objective(ScoreboardObjective {
+            name: "red_kills".to_string(),
+            action: ObjectiveAction::Create {
+                display_name: r#"{"text":"Red kills"}"#.to_string(),
+                render_type: 0,
+            },
+        });
+        scoreboard.display(DisplayScoreboard {
+            position: 3 + 12,
+            score_name: "red_kills".to_string(),
+        });
+        assert_eq!(
+            "red_kills",
+            scoreboard.sidebar_objective("Steve").unwrap().name
+        );
+        assert_eq!("kills", scoreboard.sidebar_objective("Alex").unwrap().name);
+
+        scoreboard.objective(ScoreboardObjective {
+            name: "kills".to_string(),
+            action: ObjectiveAction::Remove,
+        });
+        assert_eq!(None, scoreboard.sidebar_objective("Alex"));
+        assert_eq!(None, scoreboard.get_score("kills", "Alex"));
+    }
+
+    #[test]
+    fn test_teams() {
+        let mut scoreboard = Scoreboard::new();
+        scoreboard.team(red_team(&["Steve"]));
+        scoreboard.team(Teams {
+            name: "red".to_string(),
+            action: TeamAction::AddEntities(vec!["Alex".to_string()]),
+        });
+        scoreboard.team(Teams {
+            name: "red".to_string(),
+            action: TeamAction::RemoveEntities(vec!["Steve".to_string()]),
+        });
+        assert_eq!(None, scoreboard.team_of("Steve"));
+        assert_eq!(Some(Color::Red), scoreboard.team_of("Alex").unwrap().color);
+
+        let translations = Translations::default();
+        assert_eq!(
+            "[Red] Alex",
+            scoreboard.format_name("Alex").to_plain(&translations)
+        );
+        // the color is on the whole of prefix, name and suffix
+        assert_eq!(Some(Color::Red), scoreboard.format_name("Alex").style.color);
+
+        let mut chat =
+            ChatComponent::from_json(r#"{"translate":"chat.type.text","with":["Alex","gg"]}"#)
+                .unwrap();
+        scoreboard.decorate_chat(&mut chat);
+        assert_eq!("<[Red] Alex> gg", chat.to_plain(&translations));
+
+        // joining another team leaves the red one
+        let mut blue = red_team(&["Alex", "Notch"]);
+        blue.name = "blue".to_string();
+        scoreboard.team(blue);
+        assert_eq!("blue", scoreboard.team_of("Alex").unwrap().name);
+        assert!(scoreboard.get_team("red").unwrap().members.is_empty());
+        scoreboard.team(Teams {
+            name: "red".to_string(),
+            action: TeamAction::AddEntities(vec!["Notch".to_string()]),
+        });
+        assert_eq!("red", scoreboard.team_of("Notch").unwrap().name);
+        assert!(!scoreboard
+            .get_team("blue")
+            .unwrap()
+            .members
+            .contains("Notch"));
+
+        scoreboard.team(Teams {
+            name: "red".to_string(),
+            action: TeamAction::Remove,
+        });
+        assert_eq!(None, scoreboard.get_team("red"));
+    }
+}
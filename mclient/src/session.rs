@@ -0,0 +1,344 @@
+use std::time::Duration;
+
+use crate::packet::*;
+
+// vanilla clients send their position at least once a second even when standing still
+pub const POSITION_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+// Where the player is, as last set by the server. The client does not simulate physics,
+// it stands still wherever the server puts it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub x: f64,
+    pub y: f64, // feet
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+impl Location {
+    pub fn from_teleport(teleport: &PlayerPositionAndLook) -> Location {
+        let mut location = Location {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            on_ground: true,
+        };
+        location.teleport(teleport);
+        location
+    }
+
+    // fields with their relative flag set are added to the current value
+    pub fn teleport(&mut self, teleport: &PlayerPositionAndLook) {
+        let absolute = |flag: u8| teleport.flags & flag == 0;
+        if absolute(RELATIVE_X) {
+            self.x = 0.0;
+        }
+        if absolute(RELATIVE_Y) {
+            self.y = 0.0;
+        }
+        if absolute(RELATIVE_Z) {
+            self.z = 0.0;
+        }
+        if absolute(RELATIVE_YAW) {
+            self.yaw = 0.0;
+        }
+        if absolute(RELATIVE_PITCH) {
+            self.pitch = 0.0;
+        }
+
+        self.x += teleport.x;
+        self.y += teleport.y;
+        self.z += teleport.z;
+        self.yaw += teleport.yaw;
+        self.pitch += teleport.pitch;
+    }
+
+    pub fn position(&self) -> PlayerPosition {
+        PlayerPosition {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            on_ground: self.on_ground,
+        }
+    }
+
+    pub fn position_and_rotation(&self) -> PlayerPositionAndRotation {
+        PlayerPositionAndRotation {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            on_ground: self.on_ground,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    pub fn from_id(id: i32) -> Option<GameMode> {
+        match id {
+            0 => Some(GameMode::Survival),
+            1 => Some(GameMode::Creative),
+            2 => Some(GameMode::Adventure),
+            3 => Some(GameMode::Spectator),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Adventure => "adventure",
+            GameMode::Spectator => "spectator",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_id(id: u8) -> Option<Difficulty> {
+        match id {
+            0 => Some(Difficulty::Peaceful),
+            1 => Some(Difficulty::Easy),
+            2 => Some(Difficulty::Normal),
+            3 => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Peaceful => "peaceful",
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+// What the client knows about its own player and world, from Join Game on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub entity_id: i32,
+    pub hardcore: bool,
+    pub game_mode: Option<GameMode>,
+    pub previous_game_mode: Option<GameMode>,
+    pub dimension: String,
+    pub dimension_names: Vec<String>,
+    pub hashed_seed: i64,
+    pub max_players: i32,
+    pub view_distance: i32,
+    pub simulation_distance: i32,
+    pub difficulty: Option<Difficulty>, // None until the server sends it
+    pub difficulty_locked: bool,
+    pub raining: bool,
+    pub respawn_screen: bool,
+    pub location: Option<Location>, // None until the first Player Position And Look
+    pub health: f32,
+    pub food: i32,
+    pub saturation: f32,
+}
+
+impl Session {
+    pub fn join(join_game: &JoinGame) -> Session {
+        Session {
+            entity_id: join_game.entity_id,
+            hardcore: join_game.hardcore,
+            game_mode: GameMode::from_id(join_game.game_mode as i32),
+            previous_game_mode: GameMode::from_id(join_game.previous_game_mode as i32),
+            dimension: join_game.dimension.clone(),
+            dimension_names: join_game.dimension_names.clone(),
+            hashed_seed: join_game.hashed_seed,
+            max_players: join_game.max_players,
+            view_distance: join_game.view_distance,
+            simulation_distance: join_game.simulation_distance,
+            difficulty: None,
+            difficulty_locked: false,
+            raining: false,
+            respawn_screen: join_game.enable_respawn_screen,
+            location: None,
+            // what a new player has, the server sends Update Health when it differs
+            health: 20.0,
+            food: 20,
+            saturation: 5.0,
+        }
+    }
+
+    // a respawn after death or a change of dimension, a teleport follows it
+    pub fn respawn(&mut self, respawn: &Respawn) {
+        self.dimension = respawn.dimension.clone();
+        self.hashed_seed = respawn.hashed_seed;
+        self.game_mode = GameMode::from_id(respawn.game_mode as i32);
+        self.previous_game_mode = GameMode::from_id(respawn.previous_game_mode as i32);
+        self.location = None;
+    }
+
+    pub fn change_game_state(&mut self, change: &ChangeGameState) {
+        match change.reason {
+            BEGIN_RAINING => self.raining = true,
+            END_RAINING => self.raining = false,
+            CHANGE_GAME_MODE => {
+                self.previous_game_mode = self.game_mode;
+                self.game_mode = GameMode::from_id(change.value as i32);
+            }
+            ENABLE_RESPAWN_SCREEN => self.respawn_screen = change.value == 0.0,
+            _ => {}
+        }
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: &ServerDifficulty) {
+        self.difficulty = Difficulty::from_id(difficulty.difficulty);
+        self.difficulty_locked = difficulty.locked;
+    }
+
+    pub fn update_health(&mut self, update: &UpdateHealth) {
+        self.health = update.health;
+        self.food = update.food;
+        self.saturation = update.saturation;
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::{Compound, Tag};
+
+    #[test]
+    fn test_teleport() {
+        let mut location = Location::from_teleport(&PlayerPositionAndLook {
+            x: 10.5,
+            y: 64.0,
+            z: -3.5,
+            yaw: 90.0,
+            pitch: 10.0,
+            flags: 0,
+            teleport_id: 1,
+            dismount_vehicle: false,
+        });
+        assert_eq!((10.5, 64.0, -3.5), (location.x, location.y, location.z));
+
+        // what /tp @s ~ ~1 ~ sends: relative position and rotation, nothing added but y
+        location.teleport(&PlayerPositionAndLook {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            flags: RELATIVE_X | RELATIVE_Y | RELATIVE_Z | RELATIVE_YAW | RELATIVE_PITCH,
+            teleport_id: 2,
+            dismount_vehicle: false,
+        });
+        assert_eq!(
+            PlayerPositionAndRotation {
+                x: 10.5,
+                y: 65.0,
+                z: -3.5,
+                yaw: 90.0,
+                pitch: 10.0,
+                on_ground: true,
+            },
+            location.position_and_rotation()
+        );
+
+        location.teleport(&PlayerPositionAndLook {
+            x: 0.0,
+            y: 70.0,
+            z: 0.0,
+            yaw: -90.0,
+            pitch: 0.0,
+            flags: RELATIVE_X | RELATIVE_Z,
+            teleport_id: 3,
+            dismount_vehicle: false,
+        });
+        assert_eq!((10.5, 70.0, -3.5), (location.x, location.y, location.z));
+        assert_eq!(-90.0, location.yaw);
+    }
+
+    #[test]
+    fn test_session_updates() {
+        let mut session = Session::join(&JoinGame {
+            entity_id: 184,
+            hardcore: false,
+            game_mode: 0,
+            previous_game_mode: -1,
+            dimension_names: vec!["minecraft:overworld".to_string()],
+            dimension_codec: Tag::Compound(Compound::new()),
+            dimension_type: Tag::Compound(Compound::new()),
+            dimension: "minecraft:overworld".to_string(),
+            hashed_seed: 5,
+            max_players: 20,
+            view_distance: 10,
+            simulation_distance: 10,
+            reduced_debug_info: false,
+            enable_respawn_screen: true,
+            debug: false,
+            flat: false,
+        });
+        assert_eq!(Some(GameMode::Survival), session.game_mode);
+        assert_eq!(None, session.previous_game_mode);
+
+        session.change_game_state(&ChangeGameState {
+            reason: CHANGE_GAME_MODE,
+            value: 1.0,
+        });
+        session.change_game_state(&ChangeGameState {
+            reason: BEGIN_RAINING,
+            value: 0.0,
+        });
+        session.set_difficulty(&ServerDifficulty {
+            difficulty: 3,
+            locked: true,
+        });
+        assert_eq!(Some(GameMode::Creative), session.game_mode);
+        assert_eq!(Some(GameMode::Survival), session.previous_game_mode);
+        assert!(session.raining);
+        assert_eq!(Some(Difficulty::Hard), session.difficulty);
+
+        assert!(!session.is_dead());
+        session.update_health(&UpdateHealth {
+            health: 0.0,
+            food: 6,
+            saturation: 0.0,
+        });
+        assert!(session.is_dead());
+        assert_eq!(6, session.food);
+
+        session.respawn(&Respawn {
+            dimension_type: Tag::Compound(Compound::new()),
+            dimension: "minecraft:the_end".to_string(),
+            hashed_seed: 6,
+            game_mode: 3,
+            previous_game_mode: 1,
+            debug: false,
+            flat: false,
+            copy_metadata: false,
+        });
+        assert_eq!("minecraft:the_end", session.dimension);
+        assert_eq!(Some(GameMode::Spectator), session.game_mode);
+        assert_eq!(None, session.location);
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::{Duration, Instant};
+
+use crate::chat::ChatComponent;
+use crate::packet::TitleAction;
+
+// what the vanilla client uses until the server sends other times, in ticks
+pub const DEFAULT_FADE_IN: i32 = 10;
+pub const DEFAULT_STAY: i32 = 70;
+pub const DEFAULT_FADE_OUT: i32 = 20;
+
+const TICK: Duration = Duration::from_millis(50);
+
+// What to show for a title action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TitleEvent {
+    Title {
+        title: ChatComponent,
+        subtitle: Option<ChatComponent>,
+    },
+    // a subtitle sent while its title is on screen, servers often send it after the title
+    Subtitle(ChatComponent),
+    ActionBar(ChatComponent),
+}
+
+// The titles as the vanilla client keeps them: the subtitle waits for the next title and the
+// times say how long a title stays on screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Titles {
+    subtitle: Option<ChatComponent>,
+    pub fade_in: i32,
+    pub stay: i32,
+    pub fade_out: i32,
+    visible_until: Option<Instant>,
+}
+
+impl Default for Titles {
+    fn default() -> Self {
+        Titles {
+            subtitle: None,
+            fade_in: DEFAULT_FADE_IN,
+            stay: DEFAULT_STAY,
+            fade_out: DEFAULT_FADE_OUT,
+            visible_until: None,
+        }
+    }
+}
+
+impl Titles {
+    pub fn new() -> Titles {
+        Titles::default()
+    }
+
+    pub fn apply(&mut self, action: TitleAction) -> Option<TitleEvent> {
+        self.apply_at(action, Instant::now())
+    }
+
+    fn apply_at(&mut self, action: TitleAction, now: Instant) -> Option<TitleEvent> {
+        match action {
+            TitleAction::SetTitle(json) => {
+                let ticks = (self.fade_in + self.stay + self.fade_out).max(0) as u32;
+                self.visible_until = Some(now + TICK * ticks);
+                Some(TitleEvent::Title {
+                    title: ChatComponent::from_json_lossy(&json),
+                    subtitle: self.subtitle.clone(),
+                })
+            }
+            TitleAction::SetSubtitle(json) => {
+                let subtitle = ChatComponent::from_json_lossy(&json);
+                self.subtitle = Some(subtitle.clone());
+                match self.visible_until {
+                    Some(until) if now < until => Some(TitleEvent::Subtitle(subtitle)),
+                    _ => None,
+                }
+            }
+            TitleAction::SetActionBar(json) => {
+                Some(TitleEvent::ActionBar(ChatComponent::from_json_lossy(&json)))
+            }
+            TitleAction::SetTimes {
+                fade_in,
+                stay,
+                fade_out,
+            } => {
+                self.fade_in = fade_in;
+                self.stay = stay;
+                self.fade_out = fade_out;
+                None
+            }
+            TitleAction::Hide => {
+                self.subtitle = None;
+                self.visible_until = None;
+                None
+            }
+            TitleAction::Reset => {
+                *self = Titles::default();
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let mut titles = Titles::new();
+        let start = Instant::now();
+
+        assert_eq!(
+            None,
+            titles.apply_at(
+                TitleAction::SetSubtitle(r#""Red team wins""#.to_string()),
+                start
+            )
+        );
+        assert_eq!(
+            Some(TitleEvent::Title {
+                title: ChatComponent::text("Round over"),
+                subtitle: Some(ChatComponent::text("Red team wins")),
+            }),
+            titles.apply_at(TitleAction::SetTitle(r#""Round over""#.to_string()), start)
+        );
+
+        // shown for 10 + 70 + 20 ticks, 5 seconds
+        let subtitle = TitleAction::SetSubtitle(r#""Blue team wins""#.to_string());
+        assert_eq!(
+            Some(TitleEvent::Subtitle(ChatComponent::text("Blue team wins"))),
+            titles.apply_at(subtitle.clone(), start + Duration::from_secs(4))
+        );
+        assert_eq!(
+            None,
+            titles.apply_at(subtitle, start + Duration::from_secs(6))
+        );
+
+        titles.apply_at(
+            TitleAction::SetTimes {
+                fade_in: 0,
+                stay: 20,
+                fade_out: 0,
+            },
+            start,
+        );
+        assert_eq!(20, titles.stay);
+        titles.apply_at(TitleAction::Reset, start);
+        assert_eq!(Titles::new(), titles);
+
+        assert_eq!(
+            Some(TitleEvent::ActionBar(ChatComponent::text("+5 coins"))),
+            titles.apply_at(
+                TitleAction::SetActionBar(r#""+5 coins""#.to_string()),
+                start
+            )
+        );
+    }
+}
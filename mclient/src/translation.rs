@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+// the strings servers use the most, for when no language file is given
+const BUNDLED_EN_US: &str = include_str!("../assets/lang/en_us.json");
+
+// A Minecraft language file: translation key -> format string with %s / %1$s placeholders
+#[derive(Debug, Clone)]
+pub struct Translations {
+    table: HashMap<String, String>,
+}
+
+impl Default for Translations {
+    fn default() -> Self {
+        Translations::bundled().clone()
+    }
+}
+
+impl Translations {
+    // the bundled table, parsed once
+    pub fn bundled() -> &'static Translations {
+        static BUNDLED: OnceLock<Translations> = OnceLock::new();
+        BUNDLED.get_or_init(|| {
+            Translations::from_json(BUNDLED_EN_US).expect("the bundled language file is valid")
+        })
+    }
+
+    pub fn from_json(json: &str) -> io::Result<Translations> {
+        let table: HashMap<String, String> = serde_json::from_str(json)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        Ok(Translations { table })
+    }
+
+    // a language file like the en_us.json of the client jar, keys it misses come from the
+    // bundled table
+    pub fn load(path: &Path) -> io::Result<Translations> {
+        let json = fs::read_to_string(path)?;
+        let mut translations = Translations::default();
+        translations
+            .table
+            .extend(Translations::from_json(&json)?.table);
+        Ok(translations)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.table.get(key).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormatPiece<'a> {
+    Literal(String),
+    // index into the `with` arguments, and the placeholder to print when it is missing
+    Argument { index: usize, placeholder: &'a str },
+}
+
+// Splits a translation into literal text and arguments: %s takes the next argument,
+// %1$s a numbered one and %% is a literal percent sign
+pub fn parse_format(format: &str) -> Vec<FormatPiece<'_>> {
+    let mut pieces = vec![];
+    let mut literal = String::new();
+    let mut next_argument = 0;
+    let mut rest = format;
+
+    while let Some(start) = rest.find('%') {
+        literal.push_str(&rest[..start]);
+        let placeholder = &rest[start + 1..];
+
+        let argument = if let Some(after) = placeholder.strip_prefix('%') {
+            literal.push('%');
+            rest = after;
+            continue;
+        } else if placeholder.starts_with('s') {
+            next_argument += 1;
+            Some((next_argument - 1, 1))
+        } else {
+            let digits = placeholder
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(placeholder.len());
+            match placeholder[..digits].parse::<usize>() {
+                Ok(index) if index > 0 && placeholder[digits..].starts_with("$s") => {
+                    Some((index - 1, digits + 2))
+                }
+                _ => None,
+            }
+        };
+
+        match argument {
+            Some((index, length)) => {
+                if !literal.is_empty() {
+                    pieces.push(FormatPiece::Literal(std::mem::take(&mut literal)));
+                }
+                pieces.push(FormatPiece::Argument {
+                    index,
+                    placeholder: &rest[start..start + 1 + length],
+                });
+                rest = &placeholder[length..];
+            }
+            None => {
+                literal.push('%');
+                rest = placeholder;
+            }
+        }
+    }
+
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        pieces.push(FormatPiece::Literal(literal));
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            vec![
+                FormatPiece::Argument {
+                    index: 1,
+                    placeholder: "%2$s"
+                },
+                FormatPiece::Literal(" was slain by 100% ".to_string()),
+                FormatPiece::Argument {
+                    index: 0,
+                    placeholder: "%s"
+                },
+                FormatPiece::Literal(" %d".to_string()),
+            ],
+            parse_format("%2$s was slain by 100%% %s %d")
+        );
+        assert_eq!(Vec::<FormatPiece>::new(), parse_format(""));
+    }
+
+    #[test]
+    fn test_load() {
+        let bundled = Translations::default();
+        assert_eq!(
+            Some("%s joined the game"),
+            bundled.get("multiplayer.player.joined")
+        );
+        assert_eq!(None, bundled.get("block.minecraft.stone"));
+
+        let path = std::env::temp_dir().join(format!("mclient-lang-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"block.minecraft.stone":"Stone","multiplayer.player.joined":"%s a rejoint la partie"}"#,
+        )
+        .unwrap();
+        let loaded = Translations::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(Some("Stone"), loaded.get("block.minecraft.stone"));
+        assert_eq!(
+            Some("%s a rejoint la partie"),
+            loaded.get("multiplayer.player.joined")
+        );
+        assert_eq!(Some("<%s> %s"), loaded.get("chat.type.text"));
+        assert_eq!(bundled.len() + 1, loaded.len());
+
+        assert!(Translations::load(Path::new("/nonexistent/en_us.json")).is_err());
+        assert!(Translations::from_json("[1, 2]").is_err());
+    }
+}
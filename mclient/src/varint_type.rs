@@ -1,15 +1,22 @@
-use ::std::io;
+use ::std::io::{self, Read};
 
 const SEGMENT_BIT: i32 = 0x7F;
 const CONTINUE_BIT: i32 = 0x80;
 
-pub fn varint_read(bytes: &mut Vec<u8>) -> Result<i32, io::Error> {
+fn byte_read<R: Read>(bytes: &mut R) -> Result<u8, io::Error> {
+    let mut byte = [0u8];
+    bytes.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+// reads from anything that implements Read, e.g. &mut &[u8], a PacketReader or a stream
+pub fn varint_read<R: Read>(bytes: &mut R) -> Result<i32, io::Error> {
     let mut value = 0;
     let mut position = 0;
     let mut current_byte: u8;
 
     loop {
-        current_byte = bytes.remove(0);
+        current_byte = byte_read(bytes)?;
         value |= (current_byte as i32 & SEGMENT_BIT) << position;
 
         if (current_byte as i32 & CONTINUE_BIT) == 0 {
@@ -19,59 +26,228 @@ pub fn varint_read(bytes: &mut Vec<u8>) -> Result<i32, io::Error> {
         position += 7;
 
         if position >= 32 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "VarInt too big"));
+        }
+    }
+    Ok(value)
+}
+
+pub fn varint_write(value: i32) -> Vec<u8> {
+    let mut res: Vec<u8> = vec![];
+    let mut value = value as u32; // logical shift, so negative numbers terminate
+
+    loop {
+        if (value & !(SEGMENT_BIT as u32)) == 0 {
+            res.push(value as u8);
+            break;
+        }
+
+        res.push(((value & SEGMENT_BIT as u32) | CONTINUE_BIT as u32) as u8);
+
+        value >>= 7;
+    }
+    res
+}
+
+pub fn varlong_read<R: Read>(bytes: &mut R) -> Result<i64, io::Error> {
+    let mut value = 0;
+    let mut position = 0;
+    let mut current_byte: u8;
+
+    loop {
+        current_byte = byte_read(bytes)?;
+        value |= (current_byte as i64 & SEGMENT_BIT as i64) << position;
+
+        if (current_byte as i32 & CONTINUE_BIT) == 0 {
+            break;
+        }
+
+        position += 7;
+
+        if position >= 64 {
             return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "VarInt too big",
+                io::ErrorKind::InvalidData,
+                "VarLong too big",
             ));
         }
     }
     Ok(value)
 }
 
-pub fn varint_write(mut value: i32) -> Vec<u8> {
+pub fn varlong_write(value: i64) -> Vec<u8> {
     let mut res: Vec<u8> = vec![];
+    let mut value = value as u64;
 
     loop {
-        if (value & !SEGMENT_BIT) == 0 {
+        if (value & !(SEGMENT_BIT as u64)) == 0 {
             res.push(value as u8);
             break;
         }
 
-        res.push(((value & SEGMENT_BIT) | CONTINUE_BIT) as u8);
+        res.push(((value & SEGMENT_BIT as u64) | CONTINUE_BIT as u64) as u8);
 
         value >>= 7;
     }
     res
 }
 
+pub fn string_write(value: &str) -> Vec<u8> {
+    let mut res: Vec<u8> = varint_write(value.len() as i32);
+    res.extend_from_slice(value.as_bytes());
+    res
+}
+
+pub fn byte_array_write(value: &[u8]) -> Vec<u8> {
+    let mut res: Vec<u8> = varint_write(value.len() as i32);
+    res.extend_from_slice(value);
+    res
+}
+
+pub fn bool_write(value: bool) -> Vec<u8> {
+    vec![value as u8]
+}
+
+// zigzag maps signed numbers to unsigned ones so small negative numbers stay small:
+// 0 -> 0, -1 -> 1, 1 -> 2, -2 -> 3, ...
+pub fn zigzag_encode(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+pub fn zigzag_decode(value: u32) -> i32 {
+    ((value >> 1) as i32) ^ -((value & 1) as i32)
+}
+
+pub fn zigzag_encode_long(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub fn zigzag_decode_long(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+// block position packed in a long: x (26 bits) | z (26 bits) | y (12 bits), since 1.14
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+pub fn position_write(position: Position) -> Vec<u8> {
+    let value: i64 = ((position.x as i64 & 0x3FFFFFF) << 38)
+        | ((position.z as i64 & 0x3FFFFFF) << 12)
+        | (position.y as i64 & 0xFFF);
+    value.to_be_bytes().to_vec()
+}
+
+// rotation in steps of 1/256 of a full turn
+pub fn angle_write(degrees: f32) -> Vec<u8> {
+    let steps = (degrees * 256.0 / 360.0).round() as i64;
+    vec![steps.rem_euclid(256) as u8]
+}
+
+// fixed-point numbers with 5 fraction bits, used for entity positions before 1.9
+pub fn fixed_point_write(value: f64) -> Vec<u8> {
+    ((value * 32.0).floor() as i32).to_be_bytes().to_vec()
+}
+
+pub fn fixed_point_byte_write(value: f64) -> Vec<u8> {
+    vec![(value * 32.0).floor() as i8 as u8]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BitSet {
+    pub words: Vec<i64>,
+}
+
+impl BitSet {
+    pub fn get(&self, index: usize) -> bool {
+        self.words
+            .get(index / 64)
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        if index / 64 >= self.words.len() {
+            self.words.resize(index / 64 + 1, 0);
+        }
+        if value {
+            self.words[index / 64] |= 1 << (index % 64);
+        } else {
+            self.words[index / 64] &= !(1 << (index % 64));
+        }
+    }
+}
+
+// varint number of longs, then the longs
+pub fn bitset_write(bitset: &BitSet) -> Vec<u8> {
+    array_write(&bitset.words, |word| word.to_be_bytes().to_vec())
+}
+
+// a bitset whose size is known, sent as ceil(bits / 8) bytes without a length
+pub fn fixed_bitset_write(bitset: &BitSet, bits: usize) -> Vec<u8> {
+    let mut res = vec![0u8; bits.div_ceil(8)];
+    for index in 0..bits {
+        if bitset.get(index) {
+            res[index / 8] |= 1 << (index % 8);
+        }
+    }
+    res
+}
+
+// varint length prefixed array, each element written by the given function
+pub fn array_write<T>(values: &[T], element_write: impl Fn(&T) -> Vec<u8>) -> Vec<u8> {
+    let mut res: Vec<u8> = varint_write(values.len() as i32);
+    for value in values {
+        res.append(&mut element_write(value));
+    }
+    res
+}
+
+// boolean prefix, then the value if it is true
+pub fn option_write<T>(value: Option<&T>, element_write: impl FnOnce(&T) -> Vec<u8>) -> Vec<u8> {
+    match value {
+        Some(value) => {
+            let mut res = bool_write(true);
+            res.append(&mut element_write(value));
+            res
+        }
+        None => bool_write(false),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::reader::PacketReader;
 
     #[test]
     fn test_read() {
-        assert_eq!(0, varint_read(vec![0x00].as_mut()).unwrap());
-        assert_eq!(1, varint_read(vec![0x01].as_mut()).unwrap());
-        assert_eq!(2, varint_read(vec![0x02].as_mut()).unwrap());
-        assert_eq!(127, varint_read(vec![0x7f].as_mut()).unwrap());
-        assert_eq!(128, varint_read(vec![0x80, 0x01].as_mut()).unwrap());
-        assert_eq!(255, varint_read(vec![0xff, 0x01].as_mut()).unwrap());
-        assert_eq!(25565, varint_read(vec![0xdd, 0xc7, 0x01].as_mut()).unwrap());
+        assert_eq!(0, varint_read(&mut [0x00].as_slice()).unwrap());
+        assert_eq!(1, varint_read(&mut [0x01].as_slice()).unwrap());
+        assert_eq!(2, varint_read(&mut [0x02].as_slice()).unwrap());
+        assert_eq!(127, varint_read(&mut [0x7f].as_slice()).unwrap());
+        assert_eq!(128, varint_read(&mut [0x80, 0x01].as_slice()).unwrap());
+        assert_eq!(255, varint_read(&mut [0xff, 0x01].as_slice()).unwrap());
+        assert_eq!(
+            25565,
+            varint_read(&mut [0xdd, 0xc7, 0x01].as_slice()).unwrap()
+        );
         assert_eq!(
             2097151,
-            varint_read(vec![0xff, 0xff, 0x7f].as_mut()).unwrap()
+            varint_read(&mut [0xff, 0xff, 0x7f].as_slice()).unwrap()
         );
         assert_eq!(
             2147483647,
-            varint_read(vec![0xff, 0xff, 0xff, 0xff, 0x07].as_mut()).unwrap()
+            varint_read(&mut [0xff, 0xff, 0xff, 0xff, 0x07].as_slice()).unwrap()
         );
         assert_eq!(
             -1,
-            varint_read(vec![0xff, 0xff, 0xff, 0xff, 0x0f].as_mut()).unwrap()
+            varint_read(&mut [0xff, 0xff, 0xff, 0xff, 0x0f].as_slice()).unwrap()
         );
         assert_eq!(
             -2147483648,
-            varint_read(vec![0x80, 0x80, 0x80, 0x80, 0x08].as_mut()).unwrap()
+            varint_read(&mut [0x80, 0x80, 0x80, 0x80, 0x08].as_slice()).unwrap()
         );
     }
 
@@ -92,4 +268,199 @@ mod tests {
             varint_write(-2147483648)
         );
     }
+
+    #[test]
+    fn test_truncated() {
+        assert!(varint_read(&mut [0x80, 0x80].as_slice()).is_err());
+        // too long is a protocol error, not an i/o one
+        let error = varint_read(&mut [0xff, 0xff, 0xff, 0xff, 0xff, 0x01].as_slice()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        let error = varlong_read(&mut [0xff; 11].as_slice()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+
+    #[test]
+    fn test_varlong() {
+        let values: [(i64, Vec<u8>); 6] = [
+            (0, vec![0x00]),
+            (127, vec![0x7f]),
+            (25565, vec![0xdd, 0xc7, 0x01]),
+            (
+                9223372036854775807,
+                vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+            ),
+            (
+                -1,
+                vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+            ),
+            (
+                -9223372036854775808,
+                vec![0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+            ),
+        ];
+
+        for (value, bytes) in values {
+            assert_eq!(bytes, varlong_write(value));
+            assert_eq!(value, varlong_read(&mut bytes.as_slice()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_position() {
+        let position = Position {
+            x: 18357644,
+            y: 831,
+            z: -20882616,
+        };
+        let bytes = position_write(position);
+        assert_eq!(vec![0x46, 0x07, 0x63, 0x2c, 0x15, 0xb4, 0x83, 0x3f], bytes);
+        assert_eq!(position, PacketReader::new(&bytes).read_position().unwrap());
+
+        for position in [
+            Position { x: 0, y: 0, z: 0 },
+            Position {
+                x: -1,
+                y: -1,
+                z: -1,
+            },
+            Position {
+                x: 33554431,
+                y: 2047,
+                z: 33554431,
+            },
+            Position {
+                x: -33554432,
+                y: -2048,
+                z: -33554432,
+            },
+        ] {
+            let bytes = position_write(position);
+            assert_eq!(position, PacketReader::new(&bytes).read_position().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_angle() {
+        assert_eq!(vec![0], angle_write(0.0));
+        assert_eq!(vec![64], angle_write(90.0));
+        assert_eq!(vec![128], angle_write(180.0));
+        assert_eq!(vec![192], angle_write(-90.0));
+        assert_eq!(vec![0], angle_write(360.0));
+        assert_eq!(90.0, PacketReader::new(&[64]).read_angle().unwrap());
+        assert_eq!(358.59375, PacketReader::new(&[255]).read_angle().unwrap());
+    }
+
+    #[test]
+    fn test_bitset() {
+        let mut bitset = BitSet::default();
+        bitset.set(0, true);
+        bitset.set(65, true);
+        assert!(bitset.get(0) && bitset.get(65));
+        assert!(!bitset.get(1) && !bitset.get(1000));
+
+        let bytes = bitset_write(&bitset);
+        assert_eq!(17, bytes.len());
+        assert_eq!(0x02, bytes[0]);
+        assert_eq!(bitset, PacketReader::new(&bytes).read_bitset().unwrap());
+
+        bitset.set(65, false);
+        assert!(!bitset.get(65));
+
+        let mut fixed = BitSet::default();
+        fixed.set(0, true);
+        fixed.set(9, true);
+        let bytes = fixed_bitset_write(&fixed, 10);
+        assert_eq!(vec![0x01, 0x02], bytes);
+        assert_eq!(
+            fixed,
+            PacketReader::new(&bytes).read_fixed_bitset(10).unwrap()
+        );
+        assert!(PacketReader::new(&[0x01]).read_fixed_bitset(10).is_err());
+    }
+
+    #[test]
+    fn test_zigzag() {
+        let values: [(i32, u32); 7] = [
+            (0, 0),
+            (-1, 1),
+            (1, 2),
+            (-2, 3),
+            (2, 4),
+            (2147483647, 4294967294),
+            (-2147483648, 4294967295),
+        ];
+        for (value, encoded) in values {
+            assert_eq!(encoded, zigzag_encode(value));
+            assert_eq!(value, zigzag_decode(encoded));
+        }
+
+        assert_eq!(u64::MAX, zigzag_encode_long(i64::MIN));
+        assert_eq!(u64::MAX - 1, zigzag_encode_long(i64::MAX));
+        for value in [0, -1, 1, -300, 300, i64::MIN, i64::MAX] {
+            assert_eq!(value, zigzag_decode_long(zigzag_encode_long(value)));
+        }
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        let bytes = fixed_point_write(-1.5);
+        assert_eq!(vec![0xff, 0xff, 0xff, 0xd0], bytes);
+        assert_eq!(-1.5, PacketReader::new(&bytes).read_fixed_point().unwrap());
+        assert_eq!(
+            100.03125,
+            PacketReader::new(&fixed_point_write(100.03125))
+                .read_fixed_point()
+                .unwrap()
+        );
+
+        let bytes = fixed_point_byte_write(3.5);
+        assert_eq!(vec![112], bytes);
+        assert_eq!(
+            3.5,
+            PacketReader::new(&bytes).read_fixed_point_byte().unwrap()
+        );
+        assert_eq!(
+            -4.0,
+            PacketReader::new(&[0x80]).read_fixed_point_byte().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_array_and_option() {
+        let values = vec![1, -1, 300];
+        let bytes = array_write(&values, |value| varint_write(*value));
+        assert_eq!(
+            vec![0x03, 0x01, 0xff, 0xff, 0xff, 0xff, 0x0f, 0xac, 0x02],
+            bytes
+        );
+        assert_eq!(
+            values,
+            PacketReader::new(&bytes)
+                .read_prefixed_array(16, PacketReader::read_varint)
+                .unwrap()
+        );
+        assert!(PacketReader::new(&bytes)
+            .read_prefixed_array(2, PacketReader::read_varint)
+            .is_err());
+        assert!(PacketReader::new(&[0x02, 0x01])
+            .read_prefixed_array(16, PacketReader::read_varint)
+            .is_err());
+
+        let bytes = option_write(Some(&"hi"), |value| string_write(value));
+        assert_eq!(vec![0x01, 0x02, b'h', b'i'], bytes);
+        assert_eq!(
+            Some("hi".to_string()),
+            PacketReader::new(&bytes)
+                .read_option(PacketReader::read_string)
+                .unwrap()
+        );
+        let bytes = option_write::<i32>(None, |value| varint_write(*value));
+        assert_eq!(vec![0x00], bytes);
+        assert_eq!(
+            None,
+            PacketReader::new(&bytes)
+                .read_option(PacketReader::read_varint)
+                .unwrap()
+        );
+    }
 }
@@ -0,0 +1,410 @@
+use crate::error::{Error, Result};
+use crate::packet::{ConnectionState, Direction};
+
+// Logical packets, independent of the id they have in a given protocol version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PacketKind {
+    // handshaking
+    Handshake,
+    // status
+    StatusRequest,
+    StatusResponse,
+    PingRequest,
+    PingResponse,
+    // login
+    LoginStart,
+    LoginDisconnect,
+    EncryptionRequest,
+    EncryptionResponse,
+    LoginSuccess,
+    SetCompression,
+    LoginPluginRequest,
+    LoginPluginResponse,
+    // play, clientbound
+    BossBar,
+    ClientboundKeepAlive,
+    PlayPing,
+    ClientboundChatMessage,
+    PlayerInfo,
+    Disconnect,
+    PlayerPositionAndLook,
+    JoinGame,
+    Respawn,
+    ChangeGameState,
+    ServerDifficulty,
+    ClientboundTabComplete,
+    DeclareCommands,
+    ClientboundPluginMessage,
+    UpdateHealth,
+    CombatEvent,
+    DisplayScoreboard,
+    ScoreboardObjective,
+    Teams,
+    UpdateScore,
+    PlayerListHeaderAndFooter,
+    Title,
+    SetTitleText,
+    SetTitleSubtitle,
+    SetTitleTimes,
+    ClearTitles,
+    ActionBar,
+    // play, serverbound
+    ServerboundKeepAlive,
+    PlayPong,
+    ServerboundChatMessage,
+    TeleportConfirm,
+    PlayerPosition,
+    PlayerPositionAndRotation,
+    ClientStatus,
+    ServerboundTabComplete,
+    ClientSettings,
+    ServerboundPluginMessage,
+}
+
+impl PacketKind {
+    pub const fn state(self) -> ConnectionState {
+        use PacketKind::*;
+        match self {
+            Handshake => ConnectionState::Handshaking,
+            StatusRequest | StatusResponse | PingRequest | PingResponse => ConnectionState::Status,
+            LoginStart | LoginDisconnect | EncryptionRequest | EncryptionResponse
+            | LoginSuccess | SetCompression | LoginPluginRequest | LoginPluginResponse => {
+                ConnectionState::Login
+            }
+            _ => ConnectionState::Play,
+        }
+    }
+
+    pub const fn direction(self) -> Direction {
+        use PacketKind::*;
+        match self {
+            Handshake
+            | StatusRequest
+            | PingRequest
+            | LoginStart
+            | EncryptionResponse
+            | LoginPluginResponse
+            | ServerboundKeepAlive
+            | PlayPong
+            | ServerboundChatMessage
+            | TeleportConfirm
+            | PlayerPosition
+            | PlayerPositionAndRotation
+            | ClientStatus
+            | ServerboundTabComplete
+            | ClientSettings
+            | ServerboundPluginMessage => Direction::Serverbound,
+            _ => Direction::Clientbound,
+        }
+    }
+}
+
+type PacketTable = &'static [(PacketKind, i32)];
+
+// handshake, status and login ids did not change in the supported versions
+const HANDSHAKE_STATUS_LOGIN: PacketTable = &[
+    (PacketKind::Handshake, 0x00),
+    (PacketKind::StatusRequest, 0x00),
+    (PacketKind::StatusResponse, 0x00),
+    (PacketKind::PingRequest, 0x01),
+    (PacketKind::PingResponse, 0x01),
+    (PacketKind::LoginStart, 0x00),
+    (PacketKind::LoginDisconnect, 0x00),
+    (PacketKind::EncryptionRequest, 0x01),
+    (PacketKind::EncryptionResponse, 0x01),
+    (PacketKind::LoginSuccess, 0x02),
+    (PacketKind::SetCompression, 0x03),
+    (PacketKind::LoginPluginRequest, 0x04),
+    (PacketKind::LoginPluginResponse, 0x02),
+];
+
+const PLAY_1_16: PacketTable = &[
+    (PacketKind::BossBar, 0x0C),
+    (PacketKind::ServerDifficulty, 0x0D),
+    (PacketKind::ClientboundChatMessage, 0x0E),
+    (PacketKind::ClientboundTabComplete, 0x0F),
+    (PacketKind::DeclareCommands, 0x10),
+    (PacketKind::ClientboundPluginMessage, 0x17),
+    (PacketKind::Disconnect, 0x19),
+    (PacketKind::ChangeGameState, 0x1D),
+    (PacketKind::ClientboundKeepAlive, 0x1F),
+    (PacketKind::JoinGame, 0x24),
+    (PacketKind::CombatEvent, 0x31),
+    (PacketKind::PlayerInfo, 0x32),
+    (PacketKind::PlayerPositionAndLook, 0x34),
+    (PacketKind::Respawn, 0x39),
+    (PacketKind::DisplayScoreboard, 0x43),
+    (PacketKind::UpdateHealth, 0x49),
+    (PacketKind::ScoreboardObjective, 0x4A),
+    (PacketKind::Teams, 0x4C),
+    (PacketKind::UpdateScore, 0x4D),
+    (PacketKind::Title, 0x4F),
+    (PacketKind::PlayerListHeaderAndFooter, 0x53),
+    (PacketKind::TeleportConfirm, 0x00),
+    (PacketKind::ServerboundChatMessage, 0x03),
+    (PacketKind::ClientStatus, 0x04),
+    (PacketKind::ClientSettings, 0x05),
+    (PacketKind::ServerboundTabComplete, 0x06),
+    (PacketKind::ServerboundPluginMessage, 0x0B),
+    (PacketKind::ServerboundKeepAlive, 0x10),
+    (PacketKind::PlayerPosition, 0x12),
+    (PacketKind::PlayerPositionAndRotation, 0x13),
+];
+
+// 1.17 added Ping/Pong, split Title in five packets and Combat Event in three, of which only
+// Death Combat Event is mapped
+const PLAY_1_17: PacketTable = &[
+    (PacketKind::BossBar, 0x0D),
+    (PacketKind::ServerDifficulty, 0x0E),
+    (PacketKind::ClientboundChatMessage, 0x0F),
+    (PacketKind::ClearTitles, 0x10),
+    (PacketKind::ClientboundTabComplete, 0x11),
+    (PacketKind::DeclareCommands, 0x12),
+    (PacketKind::ClientboundPluginMessage, 0x18),
+    (PacketKind::Disconnect, 0x1A),
+    (PacketKind::ChangeGameState, 0x1E),
+    (PacketKind::ClientboundKeepAlive, 0x21),
+    (PacketKind::JoinGame, 0x26),
+    (PacketKind::PlayPing, 0x30),
+    (PacketKind::CombatEvent, 0x35),
+    (PacketKind::PlayerInfo, 0x36),
+    (PacketKind::PlayerPositionAndLook, 0x38),
+    (PacketKind::Respawn, 0x3D),
+    (PacketKind::ActionBar, 0x41),
+    (PacketKind::DisplayScoreboard, 0x4C),
+    (PacketKind::UpdateHealth, 0x52),
+    (PacketKind::ScoreboardObjective, 0x53),
+    (PacketKind::Teams, 0x55),
+    (PacketKind::UpdateScore, 0x56),
+    (PacketKind::SetTitleSubtitle, 0x57),
+    (PacketKind::SetTitleText, 0x59),
+    (PacketKind::SetTitleTimes, 0x5A),
+    (PacketKind::PlayerListHeaderAndFooter, 0x5E),
+    (PacketKind::TeleportConfirm, 0x00),
+    (PacketKind::ServerboundChatMessage, 0x03),
+    (PacketKind::ClientStatus, 0x04),
+    (PacketKind::ClientSettings, 0x05),
+    (PacketKind::ServerboundTabComplete, 0x06),
+    (PacketKind::ServerboundPluginMessage, 0x0A),
+    (PacketKind::ServerboundKeepAlive, 0x0F),
+    (PacketKind::PlayerPosition, 0x11),
+    (PacketKind::PlayerPositionAndRotation, 0x12),
+    (PacketKind::PlayPong, 0x1D),
+];
+
+// 1.18 added Update Simulation Distance at 0x57, the clientbound ids after it moved up by one
+const PLAY_1_18: PacketTable = &[
+    (PacketKind::BossBar, 0x0D),
+    (PacketKind::ServerDifficulty, 0x0E),
+    (PacketKind::ClientboundChatMessage, 0x0F),
+    (PacketKind::ClearTitles, 0x10),
+    (PacketKind::ClientboundTabComplete, 0x11),
+    (PacketKind::DeclareCommands, 0x12),
+    (PacketKind::ClientboundPluginMessage, 0x18),
+    (PacketKind::Disconnect, 0x1A),
+    (PacketKind::ChangeGameState, 0x1E),
+    (PacketKind::ClientboundKeepAlive, 0x21),
+    (PacketKind::JoinGame, 0x26),
+    (PacketKind::PlayPing, 0x30),
+    (PacketKind::CombatEvent, 0x35),
+    (PacketKind::PlayerInfo, 0x36),
+    (PacketKind::PlayerPositionAndLook, 0x38),
+    (PacketKind::Respawn, 0x3D),
+    (PacketKind::ActionBar, 0x41),
+    (PacketKind::DisplayScoreboard, 0x4C),
+    (PacketKind::UpdateHealth, 0x52),
+    (PacketKind::ScoreboardObjective, 0x53),
+    (PacketKind::Teams, 0x55),
+    (PacketKind::UpdateScore, 0x56),
+    (PacketKind::SetTitleSubtitle, 0x58),
+    (PacketKind::SetTitleText, 0x5A),
+    (PacketKind::SetTitleTimes, 0x5B),
+    (PacketKind::PlayerListHeaderAndFooter, 0x5F),
+    (PacketKind::TeleportConfirm, 0x00),
+    (PacketKind::ServerboundChatMessage, 0x03),
+    (PacketKind::ClientStatus, 0x04),
+    (PacketKind::ClientSettings, 0x05),
+    (PacketKind::ServerboundTabComplete, 0x06),
+    (PacketKind::ServerboundPluginMessage, 0x0A),
+    (PacketKind::ServerboundKeepAlive, 0x0F),
+    (PacketKind::PlayerPosition, 0x11),
+    (PacketKind::PlayerPositionAndRotation, 0x12),
+    (PacketKind::PlayPong, 0x1D),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProtocolVersion {
+    pub protocol: i32,
+    pub name: &'static str,
+    play: PacketTable,
+}
+
+pub const SUPPORTED_VERSIONS: &[ProtocolVersion] = &[
+    ProtocolVersion {
+        protocol: 754,
+        name: "1.16.5",
+        play: PLAY_1_16,
+    },
+    ProtocolVersion {
+        protocol: 755,
+        name: "1.17",
+        play: PLAY_1_17,
+    },
+    ProtocolVersion {
+        protocol: 756,
+        name: "1.17.1",
+        play: PLAY_1_17,
+    },
+    ProtocolVersion {
+        protocol: 757,
+        name: "1.18.1",
+        play: PLAY_1_18,
+    },
+    ProtocolVersion {
+        protocol: 758,
+        name: "1.18.2",
+        play: PLAY_1_18,
+    },
+];
+
+// protocol numbers where packet layouts changed
+pub const V1_17: i32 = 755;
+pub const V1_18: i32 = 757;
+
+pub const LATEST: &ProtocolVersion = &SUPPORTED_VERSIONS[SUPPORTED_VERSIONS.len() - 1];
+
+// by convention a client pinging to find out the server version sends -1
+pub const DETECT_PROTOCOL: i32 = -1;
+
+pub fn protocol_version(protocol: i32) -> Result<&'static ProtocolVersion> {
+    SUPPORTED_VERSIONS
+        .iter()
+        .find(|version| version.protocol == protocol)
+        .ok_or_else(|| {
+            let supported: Vec<String> = SUPPORTED_VERSIONS
+                .iter()
+                .map(|version| format!("{} ({})", version.name, version.protocol))
+                .collect();
+            Error::protocol(format!(
+                "Unsupported protocol version {}, supported: {}",
+                protocol,
+                supported.join(", ")
+            ))
+        })
+}
+
+// the protocol number the server reports in its status response: {"version":{"protocol":758}}
+pub fn detect_protocol(status_json: &str) -> Result<i32> {
+    let status: serde_json::Value = serde_json::from_str(status_json)
+        .map_err(|error| Error::protocol(format!("Invalid status response: {}", error)))?;
+
+    status["version"]["protocol"]
+        .as_i64()
+        .map(|protocol| protocol as i32)
+        .ok_or_else(|| Error::protocol("Status response has no version.protocol"))
+}
+
+impl ProtocolVersion {
+    fn entries(&self) -> impl Iterator<Item = &(PacketKind, i32)> {
+        HANDSHAKE_STATUS_LOGIN.iter().chain(self.play.iter())
+    }
+
+    pub fn packet_id(&self, kind: PacketKind) -> Option<i32> {
+        self.entries()
+            .find(|(entry_kind, _)| *entry_kind == kind)
+            .map(|(_, id)| *id)
+    }
+
+    pub fn packet_kind(
+        &self,
+        state: ConnectionState,
+        direction: Direction,
+        id: i32,
+    ) -> Option<PacketKind> {
+        self.entries()
+            .find(|(kind, entry_id)| {
+                *entry_id == id && kind.state() == state && kind.direction() == direction
+            })
+            .map(|(kind, _)| *kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let version = protocol_version(758).unwrap();
+        assert_eq!("1.18.2", version.name);
+        assert_eq!(LATEST, version);
+        assert_eq!(
+            Some(0x21),
+            version.packet_id(PacketKind::ClientboundKeepAlive)
+        );
+        assert_eq!(
+            Some(0x0F),
+            version.packet_id(PacketKind::ServerboundKeepAlive)
+        );
+        assert_eq!(
+            Some(PacketKind::ClientboundChatMessage),
+            version.packet_kind(ConnectionState::Play, Direction::Clientbound, 0x0F)
+        );
+        assert_eq!(
+            Some(PacketKind::ServerboundKeepAlive),
+            version.packet_kind(ConnectionState::Play, Direction::Serverbound, 0x0F)
+        );
+        assert_eq!(
+            Some(PacketKind::LoginDisconnect),
+            version.packet_kind(ConnectionState::Login, Direction::Clientbound, 0x00)
+        );
+
+        let old = protocol_version(754).unwrap();
+        assert_eq!(Some(0x1F), old.packet_id(PacketKind::ClientboundKeepAlive));
+        assert_eq!(None, old.packet_id(PacketKind::PlayPong));
+
+        assert_eq!(
+            Some(0x5E),
+            protocol_version(756)
+                .unwrap()
+                .packet_id(PacketKind::PlayerListHeaderAndFooter)
+        );
+        assert_eq!(
+            Some(0x5F),
+            version.packet_id(PacketKind::PlayerListHeaderAndFooter)
+        );
+
+        assert!(protocol_version(47).is_err());
+    }
+
+    #[test]
+    fn test_tables_are_consistent() {
+        for version in SUPPORTED_VERSIONS {
+            let entries: Vec<_> = version.entries().collect();
+            for (i, (kind, id)) in entries.iter().enumerate() {
+                for (other_kind, other_id) in &entries[i + 1..] {
+                    assert_ne!(kind, other_kind, "{:?} twice in {}", kind, version.name);
+                    assert!(
+                        id != other_id
+                            || kind.state() != other_kind.state()
+                            || kind.direction() != other_kind.direction(),
+                        "{:?} and {:?} share id {:#04x} in {}",
+                        kind,
+                        other_kind,
+                        id,
+                        version.name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_protocol() {
+        let status =
+            r#"{"version":{"name":"Paper 1.17.1","protocol":756},"players":{"max":20,"online":0}}"#;
+        assert_eq!(756, detect_protocol(status).unwrap());
+        assert!(detect_protocol(r#"{"description":"hi"}"#).is_err());
+        assert!(detect_protocol("not json").is_err());
+    }
+}